@@ -0,0 +1,60 @@
+use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_ContainerId;
+use windows::Win32::Media::Audio::eRender;
+use windows::Win32::Media::Audio::IMMDeviceEnumerator;
+use windows::Win32::Media::Audio::MMDeviceEnumerator;
+use windows::Win32::Media::Audio::DEVICE_STATE_ACTIVE;
+use windows::Win32::System::Com::CoCreateInstance;
+use windows::Win32::System::Com::StructuredStorage::PropVariantToGUID;
+use windows::Win32::System::Com::CLSCTX_ALL;
+use windows::Win32::System::Com::STGM_READ;
+
+use crate::com::take_pwstr;
+use crate::com::ComGuard;
+use crate::device::container_id_for_device_path;
+use crate::device::Device;
+use crate::error::Error;
+use crate::error::SysError;
+
+impl Device {
+    /// Returns the ID of the active render audio endpoint (e.g. the speakers of an HDMI or
+    /// DisplayPort monitor) belonging to this monitor, for use with the MMDevice API.\
+    /// The endpoint is matched by the container ID Windows uses to group the functions of a single
+    /// physical device.\
+    /// A `None` value means the monitor has no (enabled) audio endpoint.
+    pub fn audio_endpoint_id(&self) -> Result<Option<String>, Error> {
+        unsafe {
+            let Some(container_id) = container_id_for_device_path(&self.device_path)? else {
+                return Ok(None);
+            };
+
+            let _com = ComGuard::new();
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                    .map_err(SysError::AudioEndpointFailed)?;
+            let endpoints = enumerator
+                .EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)
+                .map_err(SysError::AudioEndpointFailed)?;
+
+            let count = endpoints
+                .GetCount()
+                .map_err(SysError::AudioEndpointFailed)?;
+
+            for idx in 0..count {
+                let endpoint = endpoints.Item(idx).map_err(SysError::AudioEndpointFailed)?;
+                let Ok(container) = endpoint
+                    .OpenPropertyStore(STGM_READ)
+                    .and_then(|store| store.GetValue(&PKEY_Device_ContainerId))
+                else {
+                    continue;
+                };
+
+                if PropVariantToGUID(&container).is_ok_and(|id| id == container_id) {
+                    let id = endpoint.GetId().map_err(SysError::AudioEndpointFailed)?;
+                    return Ok(Some(take_pwstr(id)));
+                }
+            }
+
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::device;
+use crate::device::Device;
+use crate::device::DeviceFilter;
+use crate::device::DeviceInfoMap;
+use crate::device::PhysicalDevice;
+use crate::error::Error;
+
+/// Caches the `DISPLAYCONFIG` data which is otherwise queried on every enumeration
+/// (`GetDisplayConfigBufferSizes` and `QueryDisplayConfig`, plus the target and source names of
+/// every path and the device path of every adapter), for callers which enumerate frequently.\
+/// The cache is never invalidated automatically: call `refresh` whenever the display
+/// configuration changes, e.g. from the callback of `watch_display_changes`. Until then, monitors
+/// connected after the last refresh have no output technology in `all`, and make `physical` fail
+/// with `Error::DeviceInfoMissing`.\
+/// Stale data can also be misattributed: the cached targets are looked up by the GDI name of
+/// their source (e.g. `\\.\DISPLAY1`), and Windows can reassign that name to another monitor
+/// after a topology change, whose devices would then get the output technology and target of the
+/// monitor which had it before.
+#[derive(Clone)]
+pub struct DisplayCache {
+    device_info_map: DeviceInfoMap,
+}
+
+impl std::fmt::Debug for DisplayCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DisplayCache")
+            .field("targets", &self.device_info_map.target_count())
+            .finish()
+    }
+}
+
+impl DisplayCache {
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self {
+            device_info_map: unsafe { device::get_device_info_map() }?,
+        })
+    }
+
+    /// Queries the `DISPLAYCONFIG` data again. The cache is left unchanged if this fails.
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        self.device_info_map = unsafe { device::get_device_info_map() }?;
+        Ok(())
+    }
+
+    /// Like `connected_displays_all`, using the cached `DISPLAYCONFIG` data
+    pub fn all(&self) -> Result<Vec<Device>, Error> {
+        device::connected_displays_all_from(self.device_info_map.clone())
+            .collect::<Result<_, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Like `connected_displays_physical`, using the cached `DISPLAYCONFIG` data
+    pub fn physical(&self) -> Result<Vec<PhysicalDevice>, Error> {
+        device::connected_displays_physical_from(
+            self.device_info_map.clone(),
+            false,
+            DeviceFilter::default(),
+        )
+        .collect::<Result<_, _>>()
+        .map_err(Into::into)
+    }
+}
@@ -0,0 +1,157 @@
+use std::ffi::c_void;
+use std::mem::size_of;
+
+use windows::Win32::Foundation::HANDLE;
+use windows::Win32::Foundation::RECT;
+use windows::Win32::Graphics::Gdi::BitBlt;
+use windows::Win32::Graphics::Gdi::CreateCompatibleDC;
+use windows::Win32::Graphics::Gdi::CreateDIBSection;
+use windows::Win32::Graphics::Gdi::DeleteDC;
+use windows::Win32::Graphics::Gdi::DeleteObject;
+use windows::Win32::Graphics::Gdi::GdiFlush;
+use windows::Win32::Graphics::Gdi::GetDC;
+use windows::Win32::Graphics::Gdi::ReleaseDC;
+use windows::Win32::Graphics::Gdi::SelectObject;
+use windows::Win32::Graphics::Gdi::BITMAPINFO;
+use windows::Win32::Graphics::Gdi::BITMAPINFOHEADER;
+use windows::Win32::Graphics::Gdi::BI_RGB;
+use windows::Win32::Graphics::Gdi::CAPTUREBLT;
+use windows::Win32::Graphics::Gdi::DIB_RGB_COLORS;
+use windows::Win32::Graphics::Gdi::HDC;
+use windows::Win32::Graphics::Gdi::SRCCOPY;
+
+use crate::device;
+use crate::error::SysError;
+
+/// A captured image as tightly packed, top-down RGBA rows
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedImage {
+    pub width: u32,
+    pub height: u32,
+    /// The virtual desktop coordinates of the top-left pixel, which are negative when a monitor
+    /// is placed above or to the left of the primary monitor
+    pub origin: (i32, i32),
+    /// `width * height * 4` bytes
+    pub pixels: Vec<u8>,
+}
+
+pub(crate) fn capture_virtual_desktop() -> Result<CapturedImage, SysError> {
+    let rects = device::connected_displays_all()
+        .map(|device| device.map(|device| device.size))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let bounds = rects
+        .iter()
+        .copied()
+        .reduce(|a, b| RECT {
+            left: a.left.min(b.left),
+            top: a.top.min(b.top),
+            right: a.right.max(b.right),
+            bottom: a.bottom.max(b.bottom),
+        })
+        .ok_or(SysError::NoDisplays)?;
+
+    let width = bounds.right - bounds.left;
+    let height = bounds.bottom - bounds.top;
+
+    unsafe {
+        let screen_dc = GetDC(None);
+        if screen_dc.is_invalid() {
+            return Err(SysError::CaptureFailed(windows::core::Error::from_win32()));
+        }
+        let memory_dc = CreateCompatibleDC(screen_dc);
+        if memory_dc.is_invalid() {
+            let error = windows::core::Error::from_win32();
+            ReleaseDC(None, screen_dc);
+            return Err(SysError::CaptureFailed(error));
+        }
+        let result = blit_monitors(screen_dc, memory_dc, &rects, bounds);
+        let _ = DeleteDC(memory_dc);
+        ReleaseDC(None, screen_dc);
+        let bgra = result?;
+
+        // Only the monitor rectangles are copied, so that the gaps between them stay transparent
+        let mut pixels = vec![0u8; bgra.len()];
+        for rect in &rects {
+            let x = (rect.left - bounds.left) as usize;
+            let row_len = (rect.right - rect.left) as usize * 4;
+            for y in (rect.top - bounds.top)..(rect.bottom - bounds.top) {
+                let start = (y as usize * width as usize + x) * 4;
+                let src = &bgra[start..start + row_len];
+                let dst = &mut pixels[start..start + row_len];
+                for (src, dst) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+                    dst.copy_from_slice(&[src[2], src[1], src[0], 0xFF]);
+                }
+            }
+        }
+
+        Ok(CapturedImage {
+            width: width as u32,
+            height: height as u32,
+            origin: (bounds.left, bounds.top),
+            pixels,
+        })
+    }
+}
+
+/// Copies every monitor rectangle from the screen into a 32bpp DIB covering `bounds`, returning
+/// its BGRA bytes
+unsafe fn blit_monitors(
+    screen_dc: HDC,
+    memory_dc: HDC,
+    rects: &[RECT],
+    bounds: RECT,
+) -> Result<Vec<u8>, SysError> {
+    let width = bounds.right - bounds.left;
+    let height = bounds.bottom - bounds.top;
+
+    let info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            // A negative height makes the DIB top-down
+            biHeight: -height,
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut bits: *mut c_void = std::ptr::null_mut();
+    let bitmap = CreateDIBSection(
+        screen_dc,
+        &info,
+        DIB_RGB_COLORS,
+        &mut bits,
+        HANDLE::default(),
+        0,
+    )
+    .map_err(SysError::CaptureFailed)?;
+    let previous = SelectObject(memory_dc, bitmap);
+
+    let result = rects.iter().try_for_each(|rect| {
+        BitBlt(
+            memory_dc,
+            rect.left - bounds.left,
+            rect.top - bounds.top,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+            screen_dc,
+            rect.left,
+            rect.top,
+            SRCCOPY | CAPTUREBLT,
+        )
+        .map_err(SysError::CaptureFailed)
+    });
+
+    let bgra = result.map(|()| {
+        let _ = GdiFlush();
+        std::slice::from_raw_parts(bits.cast::<u8>(), width as usize * height as usize * 4).to_vec()
+    });
+
+    SelectObject(memory_dc, previous);
+    let _ = DeleteObject(bitmap);
+    bgra
+}
@@ -0,0 +1,35 @@
+use windows::core::PWSTR;
+use windows::Win32::System::Com::CoInitializeEx;
+use windows::Win32::System::Com::CoTaskMemFree;
+use windows::Win32::System::Com::CoUninitialize;
+use windows::Win32::System::Com::COINIT_APARTMENTTHREADED;
+
+/// Initializes COM for the current thread for as long as it is alive.\
+/// If COM was already initialized by the caller (in any apartment), it is left untouched.
+pub(crate) struct ComGuard {
+    initialized: bool,
+}
+
+impl ComGuard {
+    pub(crate) fn new() -> Self {
+        // RPC_E_CHANGED_MODE means COM is already initialized with a different concurrency
+        // model, which is fine for our purposes as long as we don't uninitialize it
+        let initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+        Self { initialized }
+    }
+}
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        if self.initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+/// Converts a `PWSTR` allocated by COM into a `String`, freeing the original allocation
+pub(crate) unsafe fn take_pwstr(s: PWSTR) -> String {
+    let string = s.to_string().unwrap_or_default();
+    CoTaskMemFree(Some(s.0 as *const _));
+    string
+}
@@ -1,58 +1,143 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ffi::OsString;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::iter::once;
 use std::mem::size_of;
 use std::os::windows::ffi::OsStringExt;
 use std::ptr;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
 
 use itertools::Either;
+use itertools::Itertools;
+use windows::core::GUID;
 use windows::core::PCWSTR;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CM_Get_DevNode_PropertyW;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CM_Get_Device_Interface_PropertyW;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CM_Locate_DevNodeW;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CM_MapCrToWin32Err;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CM_LOCATE_DEVNODE_NORMAL;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CONFIGRET;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CR_BUFFER_SMALL;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CR_NO_SUCH_DEVICE_INTERFACE;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CR_NO_SUCH_DEVNODE;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CR_NO_SUCH_VALUE;
+use windows::Win32::Devices::DeviceAndDriverInstallation::CR_SUCCESS;
 use windows::Win32::Devices::Display::DestroyPhysicalMonitor;
 use windows::Win32::Devices::Display::DisplayConfigGetDeviceInfo;
 use windows::Win32::Devices::Display::GetDisplayConfigBufferSizes;
 use windows::Win32::Devices::Display::GetNumberOfPhysicalMonitorsFromHMONITOR;
 use windows::Win32::Devices::Display::GetPhysicalMonitorsFromHMONITOR;
 use windows::Win32::Devices::Display::QueryDisplayConfig;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_ADAPTER_NAME;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_DESKTOP_IMAGE_INFO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_DEVICE_INFO_GET_ADAPTER_NAME;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_DEVICE_INFO_GET_SDR_WHITE_LEVEL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
 use windows::Win32::Devices::Display::DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO;
 use windows::Win32::Devices::Display::DISPLAYCONFIG_MODE_INFO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_MODE_INFO_TYPE_DESKTOP_IMAGE;
 use windows::Win32::Devices::Display::DISPLAYCONFIG_MODE_INFO_TYPE_TARGET;
-use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_VIRTUAL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER;
 use windows::Win32::Devices::Display::DISPLAYCONFIG_PATH_INFO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_PATH_TARGET_INFO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_SDR_WHITE_LEVEL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_SOURCE_DEVICE_NAME;
 use windows::Win32::Devices::Display::DISPLAYCONFIG_TARGET_DEVICE_NAME;
 use windows::Win32::Devices::Display::DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_VIDEO_SIGNAL_INFO;
 use windows::Win32::Devices::Display::PHYSICAL_MONITOR;
 use windows::Win32::Devices::Display::QDC_ONLY_ACTIVE_PATHS;
+use windows::Win32::Devices::Display::QDC_VIRTUAL_MODE_AWARE;
+use windows::Win32::Devices::Display::QUERY_DISPLAY_CONFIG_FLAGS;
+use windows::Win32::Devices::Properties::DEVPKEY_Device_ContainerId;
+use windows::Win32::Devices::Properties::DEVPKEY_Device_InstanceId;
+use windows::Win32::Devices::Properties::DEVPROPTYPE;
 use windows::Win32::Foundation::CloseHandle;
 use windows::Win32::Foundation::BOOL;
 use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+use windows::Win32::Foundation::ERROR_GEN_FAILURE;
+use windows::Win32::Foundation::ERROR_PATH_NOT_FOUND;
 use windows::Win32::Foundation::ERROR_SUCCESS;
 use windows::Win32::Foundation::HANDLE;
+use windows::Win32::Foundation::HWND;
 use windows::Win32::Foundation::LPARAM;
+use windows::Win32::Foundation::LUID;
+use windows::Win32::Foundation::POINT;
 use windows::Win32::Foundation::RECT;
 use windows::Win32::Foundation::WIN32_ERROR;
 use windows::Win32::Graphics::Gdi::EnumDisplayDevicesW;
 use windows::Win32::Graphics::Gdi::EnumDisplayMonitors;
+use windows::Win32::Graphics::Gdi::EnumDisplaySettingsExW;
+use windows::Win32::Graphics::Gdi::EnumDisplaySettingsW;
 use windows::Win32::Graphics::Gdi::GetMonitorInfoW;
+use windows::Win32::Graphics::Gdi::MonitorFromPoint;
+use windows::Win32::Graphics::Gdi::DEVMODEW;
+use windows::Win32::Graphics::Gdi::DEVMODE_DISPLAY_ORIENTATION;
+use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_COLOR_ENCODING;
+use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_COLOR_ENCODING_RGB;
+use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_COLOR_ENCODING_YCBCR420;
+use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_COLOR_ENCODING_YCBCR422;
+use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_COLOR_ENCODING_YCBCR444;
 use windows::Win32::Graphics::Gdi::DISPLAY_DEVICEW;
 use windows::Win32::Graphics::Gdi::DISPLAY_DEVICE_ACTIVE;
+use windows::Win32::Graphics::Gdi::DISPLAY_DEVICE_ATTACHED;
+use windows::Win32::Graphics::Gdi::DISPLAY_DEVICE_MIRRORING_DRIVER;
+use windows::Win32::Graphics::Gdi::DISPLAY_DEVICE_MODESPRUNED;
+use windows::Win32::Graphics::Gdi::DMDO_180;
+use windows::Win32::Graphics::Gdi::DMDO_270;
+use windows::Win32::Graphics::Gdi::DMDO_90;
+use windows::Win32::Graphics::Gdi::ENUM_CURRENT_SETTINGS;
+use windows::Win32::Graphics::Gdi::ENUM_DISPLAY_SETTINGS_FLAGS;
+use windows::Win32::Graphics::Gdi::ENUM_DISPLAY_SETTINGS_MODE;
 use windows::Win32::Graphics::Gdi::HDC;
 use windows::Win32::Graphics::Gdi::HMONITOR;
 use windows::Win32::Graphics::Gdi::MONITORINFO;
 use windows::Win32::Graphics::Gdi::MONITORINFOEXW;
+use windows::Win32::Graphics::Gdi::MONITOR_DEFAULTTONULL;
 use windows::Win32::Storage::FileSystem::CreateFileW;
 use windows::Win32::Storage::FileSystem::FILE_GENERIC_READ;
 use windows::Win32::Storage::FileSystem::FILE_GENERIC_WRITE;
 use windows::Win32::Storage::FileSystem::FILE_SHARE_READ;
 use windows::Win32::Storage::FileSystem::FILE_SHARE_WRITE;
 use windows::Win32::Storage::FileSystem::OPEN_EXISTING;
+use windows::Win32::UI::HiDpi::GetDpiForMonitor;
+use windows::Win32::UI::HiDpi::MDT_EFFECTIVE_DPI;
+use windows::Win32::UI::WindowsAndMessaging::GetSystemMetrics;
+use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
 use windows::Win32::UI::WindowsAndMessaging::EDD_GET_DEVICE_INTERFACE_NAME;
+use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+use windows::Win32::UI::WindowsAndMessaging::SM_CXVIRTUALSCREEN;
+use windows::Win32::UI::WindowsAndMessaging::SM_CYVIRTUALSCREEN;
+use windows::Win32::UI::WindowsAndMessaging::SM_XVIRTUALSCREEN;
+use windows::Win32::UI::WindowsAndMessaging::SM_YVIRTUALSCREEN;
+use windows::Win32::UI::WindowsAndMessaging::USER_DEFAULT_SCREEN_DPI;
 
+use crate::dxgi;
+use crate::dxgi::LuminanceRange;
+use crate::edid;
+use crate::edid::AudioFormat;
+use crate::edid::EdidInfo;
+use crate::edid::FrequencyRanges;
+use crate::edid::HdrStaticMetadata;
+use crate::error::Error;
 use crate::error::SysError;
+use crate::output_technology::ConnectorType;
+use crate::vcp;
+use crate::vcp::VcpReply;
 
 #[derive(Debug)]
 pub struct PhysicalDevice {
     // new stuff
-    pub hmonitor: isize,
+    pub hmonitor: MonitorHandle,
     pub size: RECT,
     pub work_area_size: RECT,
     // old stuff
@@ -62,17 +147,49 @@ pub struct PhysicalDevice {
     /// Note: PHYSICAL_MONITOR.szPhysicalMonitorDescription == DISPLAY_DEVICEW.DeviceString
     /// Description is **not** unique.
     pub device_description: String,
+    /// The name of the monitor from `DISPLAYCONFIG_TARGET_DEVICE_NAME.monitorFriendlyDeviceName`
+    /// (e.g. "DELL U2720Q"), falling back to `device_description` when it is empty or unknown.
+    pub friendly_name: String,
     pub device_key: String,
     /// Note: DISPLAYCONFIG_TARGET_DEVICE_NAME.monitorDevicePath == DISPLAY_DEVICEW.DeviceID (with EDD_GET_DEVICE_INTERFACE_NAME)\
     /// These are in the "DOS Device Path" format.
     pub device_path: String,
     pub output_technology: DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY,
+    /// The orientation of the current display mode, `Orientation::Landscape` when it is unknown
+    pub orientation: Orientation,
+    /// The `(LowPart, HighPart)` of the adapter LUID of this monitor's `DISPLAYCONFIG` target, for
+    /// further `DisplayConfigGetDeviceInfo` queries.\
+    /// Like `output_technology`, this is zeroed when the target is unknown in lenient mode.
+    pub adapter_id: (u32, i32),
+    /// The id of this monitor's `DISPLAYCONFIG` target on its adapter
+    pub target_id: u32,
+    /// The refresh rate of the current display mode in Hz, 0 when it is unknown
+    pub refresh_rate_hz: u32,
+    /// The `(width, height)` of the current display mode in pixels, `(0, 0)` when it is unknown
+    pub resolution: (u32, u32),
+    /// The effective DPI of the monitor, which is 96 unless the process is per-monitor DPI aware
+    pub dpi: u32,
+    /// The effective scale factor, where 96 DPI is `1.0`
+    pub scale_factor: f64,
+    /// Whether this monitor is the primary monitor (`MONITORINFOF_PRIMARY`).\
+    /// The flag belongs to the `HMONITOR`, so devices in "Duplicate" mode report the same value.
+    pub is_primary: bool,
+    /// Whether other active display devices share this device's `HMONITOR`, i.e. it is mirrored in
+    /// "Duplicate" mode (see `duplicate_groups` for the devices it is mirrored with).
+    pub is_duplicated: bool,
+    /// The result of `supports_ddcci`, which is only probed once
+    ddcci_supported: OnceLock<bool>,
 }
 
-#[derive(Debug)]
+/// Two devices are equal (and hash the same) when they have the same `device_path`, which is the
+/// most stable identifier of a monitor: the other fields describe its current state, which can
+/// change without it becoming a different monitor (e.g. after moving it or changing its mode).\
+/// Devices without a `device_path` (e.g. some virtual displays) are compared by their `hmonitor`
+/// and `device_name` instead, and are never equal to a device which has one.
+#[derive(Clone, Debug)]
 pub struct Device {
     // new stuff
-    pub hmonitor: isize,
+    pub hmonitor: MonitorHandle,
     pub size: RECT,
     pub work_area_size: RECT,
     // old stuff
@@ -80,16 +197,910 @@ pub struct Device {
     /// Note: PHYSICAL_MONITOR.szPhysicalMonitorDescription == DISPLAY_DEVICEW.DeviceString
     /// Description is **not** unique.
     pub device_description: String,
+    /// The name of the monitor from `DISPLAYCONFIG_TARGET_DEVICE_NAME.monitorFriendlyDeviceName`
+    /// (e.g. "DELL U2720Q"), falling back to `device_description` when it is empty or unknown.
+    pub friendly_name: String,
     pub device_key: String,
     /// Note: DISPLAYCONFIG_TARGET_DEVICE_NAME.monitorDevicePath == DISPLAY_DEVICEW.DeviceID (with EDD_GET_DEVICE_INTERFACE_NAME)\
     /// These are in the "DOS Device Path" format.
     pub device_path: String,
     pub output_technology: Option<DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY>,
+    /// The orientation of the current display mode, `Orientation::Landscape` when it is unknown
+    pub orientation: Orientation,
+    /// The device instance path of the GPU driving this monitor, from
+    /// `DISPLAYCONFIG_ADAPTER_NAME.adapterDevicePath`, for matching against Device Manager.
+    pub adapter_device_path: Option<String>,
+    /// The name of the GPU driving this monitor (e.g. "NVIDIA GeForce RTX 3060 Laptop GPU"), from
+    /// the `DeviceString` of the adapter whose GDI device name this monitor's name starts with.\
+    /// This tells apart the GPUs of laptops with hybrid graphics. An empty value means the adapter
+    /// could not be found.
+    pub adapter_name: String,
+    /// The `(LowPart, HighPart)` of the adapter LUID of this monitor's `DISPLAYCONFIG` target, for
+    /// further `DisplayConfigGetDeviceInfo` queries. The LUID is stable until the adapter is
+    /// restarted (e.g. by a driver update or reboot).\
+    /// A `None` value means the target is unknown, as with `output_technology`.
+    pub adapter_id: Option<(u32, i32)>,
+    /// The id of this monitor's `DISPLAYCONFIG` target on its adapter
+    pub target_id: Option<u32>,
+    /// Whether HDR ("advanced color") is currently enabled on this monitor
+    pub hdr_enabled: bool,
+    /// The brightness SDR content is shown with, as set with the "SDR content brightness"
+    /// slider.\
+    /// A `None` value means advanced color is not supported, or the target is unknown.
+    pub sdr_white_level_nits: Option<f32>,
+    /// The bit depth of each color channel of the signal sent to the monitor (e.g. 10 on many HDR
+    /// monitors), which is 8 when it can't be queried.\
+    /// Unlike `DEVMODEW.dmBitsPerPel`, which is 32 for both, this tells apart 8-bit and 10-bit
+    /// output.
+    pub bits_per_color_channel: u32,
+    /// The refresh rate of the current display mode in Hz, 0 when it is unknown
+    pub refresh_rate_hz: u32,
+    /// The `(width, height)` of the current display mode in pixels, `(0, 0)` when it is unknown.\
+    /// Devices in "Duplicate" mode share a source, and therefore report the same mode.
+    pub resolution: (u32, u32),
+    /// The manufacturer, product code and serial number from the monitor's EDID, for identifying
+    /// it across reboots and port changes.\
+    /// A `None` value means the monitor does not publish an EDID (e.g. virtual displays) or it
+    /// could not be read or parsed, which doesn't fail the enumeration of the device. Use
+    /// `Device::raw_edid` to see why a published EDID could not be read.
+    pub edid: Option<EdidInfo>,
+    /// The `(width, height)` of the panel in millimeters, from the EDID basic display parameters
+    /// (which are only precise to the centimeter).\
+    /// A `None` value means the monitor doesn't declare its size, e.g. projectors and virtual
+    /// displays.
+    pub physical_size_mm: Option<(u32, u32)>,
+    /// The effective DPI of the monitor, which is 96 unless the process is per-monitor DPI aware
+    pub dpi: u32,
+    /// The effective scale factor, where 96 DPI is `1.0`
+    pub scale_factor: f64,
+    /// Whether this monitor is the primary monitor (`MONITORINFOF_PRIMARY`).\
+    /// The flag belongs to the `HMONITOR`, so devices in "Duplicate" mode report the same value.
+    pub is_primary: bool,
+    /// Whether other active display devices share this device's `HMONITOR`, i.e. it is mirrored in
+    /// "Duplicate" mode (see `duplicate_groups` for the devices it is mirrored with).
+    pub is_duplicated: bool,
+    /// Whether this monitor is currently showing the desktop (`DISPLAY_DEVICE_ACTIVE`). Inactive
+    /// monitors are only returned by `connected_displays_all_including_inactive`, and have no
+    /// `HMONITOR`, an empty `size` and `work_area_size`, and no display mode.
+    pub is_active: bool,
+    /// Whether this monitor is attached to the desktop (`DISPLAY_DEVICE_ATTACHED`), even if it is
+    /// not currently showing it, e.g. a docked monitor which is switched off in "Duplicate" mode.\
+    /// Every inactive monitor returned by `connected_displays_all_including_inactive` is attached,
+    /// since monitors which are neither are leftovers of ones connected to the output before.
+    pub is_attached: bool,
+}
+
+/// Devices which show the same desktop in "Duplicate" mode, see `duplicate_groups`
+#[derive(Clone, Debug)]
+pub struct DuplicateGroup {
+    pub devices: Vec<Device>,
+    /// The index in `devices` of the clone's primary source, whose settings take effect
+    pub primary: usize,
+}
+
+impl DuplicateGroup {
+    /// The device driving the clone
+    pub fn primary(&self) -> &Device {
+        &self.devices[self.primary]
+    }
+}
+
+/// The physical monitors showing a single `HMONITOR`, see `grouped_displays`.\
+/// Groups with more than one device are monitors in "Duplicate" mode.
+#[derive(Debug)]
+pub struct MonitorGroup {
+    pub hmonitor: MonitorHandle,
+    pub devices: Vec<PhysicalDevice>,
+}
+
+/// The timing of the video signal sent to a monitor, see `Device::signal_mode`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignalMode {
+    /// The visible `(width, height)` of the signal in pixels
+    pub active: (u32, u32),
+    /// The `(width, height)` of the signal including blanking
+    pub total: (u32, u32),
+    pub pixel_rate_hz: u64,
+    /// The vertical refresh rate in Hz
+    pub v_sync: f64,
 }
 
+/// A snapshot of a monitor's geometry and DPI, read together so that they are consistent.\
+/// Rectangles are `(left, top, right, bottom)` in virtual desktop coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometry {
+    pub bounds: (i32, i32, i32, i32),
+    pub work_area: (i32, i32, i32, i32),
+    /// The effective `(x, y)` DPI
+    pub dpi: (u32, u32),
+    /// The effective scale factor, where 96 DPI is `1.0`
+    pub scale: f64,
+}
+
+/// The descriptive fields of a `PhysicalDevice` without its live handles, for logging and
+/// persisting.\
+/// Rectangles are `(left, top, right, bottom)` and the output technology is the raw
+/// `DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY` value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayInfo {
+    pub hmonitor: isize,
+    pub size: (i32, i32, i32, i32),
+    pub work_area_size: (i32, i32, i32, i32),
+    pub device_name: String,
+    pub device_description: String,
+    pub device_key: String,
+    pub device_path: String,
+    pub output_technology: i32,
+}
+
+/// The position of a monitor in a grid of the monitor arrangement, relative to the primary monitor
+/// at `(0, 0)`, see `layout`.\
+/// Columns grow to the right and rows grow downwards, like virtual desktop coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutCell {
+    pub col: i32,
+    pub row: i32,
+}
+
+/// The color format (encoding) of the signal sent to a display.\
+/// TVs often default to `YCbCr422`, which makes text look fuzzy compared to `Rgb`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorFormat {
+    Rgb,
+    YCbCr444,
+    YCbCr422,
+    YCbCr420,
+}
+
+impl TryFrom<DISPLAYCONFIG_COLOR_ENCODING> for ColorFormat {
+    type Error = DISPLAYCONFIG_COLOR_ENCODING;
+
+    fn try_from(encoding: DISPLAYCONFIG_COLOR_ENCODING) -> Result<Self, Self::Error> {
+        match encoding {
+            DISPLAYCONFIG_COLOR_ENCODING_RGB => Ok(Self::Rgb),
+            DISPLAYCONFIG_COLOR_ENCODING_YCBCR444 => Ok(Self::YCbCr444),
+            DISPLAYCONFIG_COLOR_ENCODING_YCBCR422 => Ok(Self::YCbCr422),
+            DISPLAYCONFIG_COLOR_ENCODING_YCBCR420 => Ok(Self::YCbCr420),
+            e => Err(e),
+        }
+    }
+}
+
+/// The orientation of a display, from `DEVMODEW.dmDisplayOrientation`.\
+/// Each step rotates the desktop image a further 90 degrees clockwise relative to the panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    Portrait,
+    LandscapeFlipped,
+    PortraitFlipped,
+}
+
+impl From<DEVMODE_DISPLAY_ORIENTATION> for Orientation {
+    fn from(orientation: DEVMODE_DISPLAY_ORIENTATION) -> Self {
+        match orientation {
+            DMDO_90 => Self::Portrait,
+            DMDO_180 => Self::LandscapeFlipped,
+            DMDO_270 => Self::PortraitFlipped,
+            _ => Self::Landscape,
+        }
+    }
+}
+
+/// An edge of a monitor
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Edge {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+impl Edge {
+    /// Returns the edge of the panel which this edge of the desktop image is shown on, when the
+    /// image is displayed with the given orientation.
+    pub fn rotated(self, orientation: Orientation) -> Self {
+        let clockwise = |edge| match edge {
+            Self::Top => Self::Right,
+            Self::Right => Self::Bottom,
+            Self::Bottom => Self::Left,
+            Self::Left => Self::Top,
+        };
+
+        match orientation {
+            Orientation::Landscape => self,
+            Orientation::Portrait => clockwise(self),
+            Orientation::LandscapeFlipped => clockwise(clockwise(self)),
+            Orientation::PortraitFlipped => clockwise(clockwise(clockwise(self))),
+        }
+    }
+}
+
+/// Implements the size accessors shared by `Device` and `PhysicalDevice`
+macro_rules! impl_size_accessors {
+    ($ty:ty) => {
+        impl $ty {
+            /// The width of the monitor in virtual desktop coordinates
+            pub fn width(&self) -> i32 {
+                self.size.right - self.size.left
+            }
+
+            /// The height of the monitor in virtual desktop coordinates
+            pub fn height(&self) -> i32 {
+                self.size.bottom - self.size.top
+            }
+
+            /// The width of the work area, i.e. the monitor without the taskbar and docked app bars
+            pub fn work_area_width(&self) -> i32 {
+                self.work_area_size.right - self.work_area_size.left
+            }
+
+            /// The height of the work area, i.e. the monitor without the taskbar and docked app
+            /// bars
+            pub fn work_area_height(&self) -> i32 {
+                self.work_area_size.bottom - self.work_area_size.top
+            }
+
+            /// The `(x, y)` of the top-left corner of the monitor in virtual desktop coordinates
+            pub fn origin(&self) -> (i32, i32) {
+                (self.size.left, self.size.top)
+            }
+        }
+    };
+}
+
+impl_size_accessors!(PhysicalDevice);
+impl_size_accessors!(Device);
+
 impl PhysicalDevice {
+    /// Whether this is a built-in panel, e.g. of a laptop, i.e. its `ConnectorType` is `Internal`.
     pub fn is_internal(&self) -> bool {
-        self.output_technology == DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL
+        self.connector_type() == ConnectorType::Internal
+    }
+
+    /// Returns the parsed connector type of this monitor.
+    pub fn connector_type(&self) -> ConnectorType {
+        self.output_technology.into()
+    }
+
+    /// Returns a copy of the descriptive fields of this device, without the handles.
+    pub fn metadata(&self) -> DisplayInfo {
+        let rect = |r: RECT| (r.left, r.top, r.right, r.bottom);
+        DisplayInfo {
+            hmonitor: self.hmonitor.as_raw(),
+            size: rect(self.size),
+            work_area_size: rect(self.work_area_size),
+            device_name: self.device_name.clone(),
+            device_description: self.device_description.clone(),
+            device_key: self.device_key.clone(),
+            device_path: self.device_path.clone(),
+            output_technology: self.output_technology.0,
+        }
+    }
+
+    /// Returns the color format currently sent to this monitor, from the
+    /// `DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO` of its active path.\
+    /// Note: This requires Windows 10 1709 or later.
+    pub fn color_format(&self) -> Result<ColorFormat, Error> {
+        unsafe {
+            let target = active_target_for_device_path(&self.device_path)?
+                .ok_or(SysError::DeviceInfoMissing)?;
+            let encoding = get_advanced_color_info(target.adapterId, target.id)?.colorEncoding;
+            Ok(ColorFormat::try_from(encoding)
+                .map_err(|e| SysError::UnsupportedColorEncoding(e.0))?)
+        }
+    }
+
+    /// Requests a different color format from the monitor over DDC/CI.\
+    /// There is no standard MCCS code for the color format, so both the VCP code and the value
+    /// must come from the vendor's documentation: they are manufacturer specific (usually in the
+    /// `0xE0..=0xFF` range), differ between models of the same vendor, and many monitors don't
+    /// expose this over DDC/CI at all. Read back `color_format` to verify the change, as the GPU
+    /// driver may also need to renegotiate the signal.
+    pub fn set_color_format(&self, vcp_code: u8, value: u32) -> Result<(), Error> {
+        Ok(unsafe { vcp::set_vcp_feature(self.physical_monitor.0, vcp_code, value) }?)
+    }
+
+    /// Re-enumerates the displays to verify that this monitor is still attached to its `HMONITOR`.\
+    /// The cached `physical_monitor` handle is invalidated when a monitor is disconnected, and
+    /// DDC/CI calls on it will fail with obscure I2C errors, so long-lived holders of a
+    /// `PhysicalDevice` should check this before issuing VCP commands.
+    pub fn is_connected(&self) -> bool {
+        unsafe {
+            let hmonitor = HMONITOR::from(self.hmonitor);
+            enum_display_monitors().is_ok_and(|hmonitors| hmonitors.contains(&hmonitor))
+                && get_active_display_devices_from_hmonitor(hmonitor).is_ok_and(|display_devices| {
+                    display_devices.iter().any(|(_, display_device)| {
+                        wchar_to_string(&display_device.DeviceID) == self.device_path
+                    })
+                })
+        }
+    }
+
+    /// Re-acquires the `physical_monitor` and `file_handle` of this device from its stored
+    /// `HMONITOR` and `device_path`, dropping the old handles, e.g. when DDC/CI calls start failing
+    /// after a display change. The cached `supports_ddcci` result is reset as well.\
+    /// Fails with `Error::MonitorHandleStale` when the `HMONITOR` is no longer valid, and with
+    /// `Error::DeviceDisconnected` when the monitor is no longer attached to it, in which case this
+    /// `PhysicalDevice` should be dropped.
+    pub fn reopen(&mut self) -> Result<(), Error> {
+        unsafe {
+            let hmonitor = HMONITOR::from(self.hmonitor);
+            get_monitor_info(hmonitor).map_err(|_| SysError::MonitorHandleStale)?;
+            let disconnected = || SysError::DeviceDisconnected {
+                device_path: self.device_path.clone(),
+            };
+            let (physical_monitor, display_device) =
+                physical_monitor_for_path(hmonitor, &self.device_path)?.ok_or_else(disconnected)?;
+            let file_handle =
+                get_file_handle_for_display_device(&display_device)?.ok_or_else(disconnected)?;
+
+            self.physical_monitor = physical_monitor;
+            self.file_handle = file_handle;
+            self.ddcci_supported = OnceLock::new();
+            Ok(())
+        }
+    }
+
+    /// Reads the current color temperature of this monitor in Kelvin over DDC/CI.\
+    /// A `None` value means the monitor doesn't report a color temperature.\
+    /// Check `is_connected` first if this `PhysicalDevice` may have outlived a hotplug.
+    pub fn color_temperature(&self) -> Result<Option<u32>, Error> {
+        Ok(unsafe { vcp::get_color_temperature(self.physical_monitor.0) }?)
+    }
+
+    /// Checks whether the monitor actually responds over DDC/CI, by asking for the length of its
+    /// capabilities string. A `physical_monitor` handle is returned even for monitors without
+    /// DDC/CI, Remote Desktop and virtual displays, which all report `false`.\
+    /// The probe can take a moment, so its result is cached for the lifetime of this
+    /// `PhysicalDevice`.
+    pub fn supports_ddcci(&self) -> bool {
+        *self.ddcci_supported.get_or_init(|| unsafe {
+            vcp::get_capabilities_string_length(self.physical_monitor.0).is_ok()
+        })
+    }
+
+    /// Reads the raw MCCS capabilities string of the monitor, which lists the VCP codes it
+    /// implements (and their possible values for non-continuous features), e.g.
+    /// `(prot(monitor)type(lcd)cmds(01 02 03 0C)vcp(10 12 14(05 08 0B) 60(0F 11 12))mccs_ver(2.1))`.\
+    /// Monitors which don't respond over DDC/CI, or reply with an empty string, fail with
+    /// `Error::DdcCiFailed`. Requesting the capabilities can take a few seconds on some monitors.
+    pub fn capabilities_string(&self) -> Result<String, Error> {
+        let capabilities = unsafe { vcp::get_capabilities_string(self.physical_monitor.0) };
+        // This is a stronger probe than the one of supports_ddcci
+        let _ = self.ddcci_supported.set(capabilities.is_ok());
+        Ok(capabilities?)
+    }
+
+    /// Reads any VCP feature over DDC/CI, e.g. the input source (`0x60`).\
+    /// Fails with an unsupported error when the monitor rejects the feature. Monitors without
+    /// working DDC/CI often report success with a current and maximum value of zero instead, which
+    /// is reported as an error rather than as a valid reply.\
+    /// Check `is_connected` first if this `PhysicalDevice` may have outlived a hotplug.
+    pub fn get_vcp_feature(&self, code: u8) -> Result<VcpReply, Error> {
+        let reply = unsafe { vcp::get_vcp_feature(self.physical_monitor.0, code) }?
+            .ok_or(SysError::VcpFeatureUnsupported { code })?;
+        if reply.current == 0 && reply.maximum == 0 {
+            return Err(SysError::VcpReplyEmpty { code }.into());
+        }
+        Ok(reply)
+    }
+
+    /// Writes any VCP feature over DDC/CI, e.g. the input source (`0x60`) to switch the monitor to
+    /// another input. The MCCS input source values include `0x0F` for DisplayPort 1 and `0x11` for
+    /// HDMI 1, but vendors commonly deviate from them.\
+    /// Not every monitor accepts writes, even to features it can read, in which case this fails
+    /// with `Error::DdcCiWriteFailed`.\
+    /// Check `is_connected` first if this `PhysicalDevice` may have outlived a hotplug.
+    pub fn set_vcp_feature(&self, code: u8, value: u32) -> Result<(), Error> {
+        Ok(unsafe { vcp::set_vcp_feature(self.physical_monitor.0, code, value) }?)
+    }
+
+    /// Reads the "Display Scaling" VCP feature (`0x86`), which TVs commonly use to control
+    /// overscan.\
+    /// The MCCS values most relevant to overscan are:
+    /// - `0x01` no scaling, i.e. 1:1 pixel mapping (often labelled "Just Scan", "Screen Fit" or
+    ///   "Full Pixel"), which disables overscan
+    /// - `0x02` maximum image without aspect ratio distortion
+    /// - `0x08` linear expansion of both axes, which is what most TVs do when overscanning
+    ///
+    /// Vendors may implement overscan through their own codes instead, in which case this returns
+    /// an unsupported error.
+    pub fn get_overscan(&self) -> Result<VcpReply, Error> {
+        let code = vcp::DISPLAY_SCALING;
+        let reply = unsafe { vcp::get_vcp_feature(self.physical_monitor.0, code) }?;
+        Ok(reply.ok_or(SysError::VcpFeatureUnsupported { code })?)
+    }
+
+    /// Sets the "Display Scaling" VCP feature (`0x86`), see `get_overscan` for common values.\
+    /// Check `is_connected` first if this `PhysicalDevice` may have outlived a hotplug.
+    pub fn set_overscan(&self, value: u32) -> Result<(), Error> {
+        Ok(unsafe { vcp::set_vcp_feature(self.physical_monitor.0, vcp::DISPLAY_SCALING, value) }?)
+    }
+}
+
+/// Copies the fields both types have, without any Win32 calls, so that the handles of the
+/// `PhysicalDevice` can be released while keeping the descriptive data.\
+/// The fields which only `Device` has (e.g. `edid` and `hdr_enabled`) are left empty or at their
+/// defaults, and a zeroed `adapter_id` (see `PhysicalDevice::adapter_id`) becomes `None`.
+impl From<&PhysicalDevice> for Device {
+    fn from(device: &PhysicalDevice) -> Self {
+        let target_known = device.adapter_id != (0, 0);
+
+        Self {
+            hmonitor: device.hmonitor,
+            size: device.size,
+            work_area_size: device.work_area_size,
+            device_name: device.device_name.clone(),
+            device_description: device.device_description.clone(),
+            friendly_name: device.friendly_name.clone(),
+            device_key: device.device_key.clone(),
+            device_path: device.device_path.clone(),
+            output_technology: Some(device.output_technology),
+            orientation: device.orientation,
+            adapter_device_path: None,
+            adapter_name: String::new(),
+            adapter_id: target_known.then_some(device.adapter_id),
+            target_id: target_known.then_some(device.target_id),
+            hdr_enabled: false,
+            sdr_white_level_nits: None,
+            bits_per_color_channel: DEFAULT_BITS_PER_COLOR_CHANNEL,
+            refresh_rate_hz: device.refresh_rate_hz,
+            resolution: device.resolution,
+            edid: None,
+            physical_size_mm: None,
+            dpi: device.dpi,
+            scale_factor: device.scale_factor,
+            is_primary: device.is_primary,
+            is_duplicated: device.is_duplicated,
+            is_active: true,
+            is_attached: true,
+        }
+    }
+}
+
+impl Device {
+    /// The identity compared by `PartialEq` and `Hash`, see the docs on `Device`
+    fn identity(&self) -> (&str, Option<(MonitorHandle, &str)>) {
+        let fallback = self
+            .device_path
+            .is_empty()
+            .then_some((self.hmonitor, self.device_name.as_str()));
+        (&self.device_path, fallback)
+    }
+}
+
+impl PartialEq for Device {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for Device {}
+
+impl Hash for Device {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state);
+    }
+}
+
+impl Device {
+    /// Fabricates a `Device` without any Win32 calls, for use in tests of code consuming this
+    /// crate.\
+    /// Fields which are not passed in are left empty, and the output technology is unknown.
+    #[doc(hidden)]
+    pub fn new_for_test(
+        hmonitor: MonitorHandle,
+        size: RECT,
+        work_area_size: RECT,
+        device_name: impl Into<String>,
+        device_path: impl Into<String>,
+    ) -> Self {
+        Self {
+            hmonitor,
+            size,
+            work_area_size,
+            device_name: device_name.into(),
+            device_description: String::new(),
+            friendly_name: String::new(),
+            device_key: String::new(),
+            device_path: device_path.into(),
+            output_technology: None,
+            orientation: Orientation::default(),
+            adapter_device_path: None,
+            adapter_name: String::new(),
+            adapter_id: None,
+            target_id: None,
+            hdr_enabled: false,
+            sdr_white_level_nits: None,
+            bits_per_color_channel: DEFAULT_BITS_PER_COLOR_CHANNEL,
+            refresh_rate_hz: 0,
+            edid: None,
+            physical_size_mm: None,
+            resolution: (
+                (size.right - size.left) as u32,
+                (size.bottom - size.top) as u32,
+            ),
+            dpi: USER_DEFAULT_SCREEN_DPI,
+            scale_factor: 1.0,
+            is_primary: size.left == 0 && size.top == 0,
+            is_duplicated: false,
+            is_active: true,
+            is_attached: true,
+        }
+    }
+
+    /// Re-reads the parts of this device which change without it being reconnected (bounds, work
+    /// area, display mode, DPI and primary flag) from its stored `HMONITOR`, which is much cheaper
+    /// than enumerating every display, e.g. on `WM_DISPLAYCHANGE`.\
+    /// Fails with `Error::MonitorHandleStale` when the `HMONITOR` is no longer valid, in which case
+    /// the displays need to be enumerated again.
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        unsafe {
+            let hmonitor = HMONITOR::from(self.hmonitor);
+            let info = get_monitor_info(hmonitor).map_err(|_| SysError::MonitorHandleStale)?;
+            let mode = get_current_display_settings(&info.szDevice).unwrap_or_default();
+            let (dpi, scale_factor) = get_scale_for_monitor(hmonitor);
+
+            self.size = info.monitorInfo.rcMonitor;
+            self.work_area_size = info.monitorInfo.rcWork;
+            self.orientation = Orientation::from(mode.Anonymous1.Anonymous2.dmDisplayOrientation);
+            self.refresh_rate_hz = mode.dmDisplayFrequency;
+            self.resolution = (mode.dmPelsWidth, mode.dmPelsHeight);
+            self.dpi = dpi;
+            self.scale_factor = scale_factor;
+            self.is_primary = flag_set(info.monitorInfo.dwFlags, MONITORINFOF_PRIMARY);
+            Ok(())
+        }
+    }
+
+    /// Reads the current bounds, work area and effective DPI of this monitor in a single pass.
+    pub fn geometry(&self) -> Result<Geometry, Error> {
+        unsafe {
+            let hmonitor = HMONITOR::from(self.hmonitor);
+            let info = get_monitor_info(hmonitor)?.monitorInfo;
+            let dpi = get_dpi_for_monitor(hmonitor)?;
+            let rect = |r: RECT| (r.left, r.top, r.right, r.bottom);
+
+            Ok(Geometry {
+                bounds: rect(info.rcMonitor),
+                work_area: rect(info.rcWork),
+                dpi,
+                scale: f64::from(dpi.0) / f64::from(USER_DEFAULT_SCREEN_DPI),
+            })
+        }
+    }
+
+    /// Reads the effective `(x, y)` DPI of this monitor
+    pub(crate) fn dpi(&self) -> Result<(u32, u32), SysError> {
+        unsafe { get_dpi_for_monitor(HMONITOR::from(self.hmonitor)) }
+    }
+
+    /// Returns the work area as `(left, top, right, bottom)` in physical pixels, by scaling it with
+    /// the monitor's effective DPI.\
+    /// Only the offset from the monitor's origin and the extents are scaled, the origin itself is
+    /// kept, so monitors at negative coordinates (left of or above the primary) stay in place
+    /// instead of being pushed further away from it.
+    pub fn work_area_physical(&self) -> Result<(i32, i32, i32, i32), Error> {
+        let geometry = self.geometry()?;
+        let (origin_x, origin_y, _, _) = geometry.bounds;
+        let (left, top, right, bottom) = geometry.work_area;
+        let scale = |offset: i32| (f64::from(offset) * geometry.scale).round() as i32;
+
+        let left = origin_x + scale(left - origin_x);
+        let top = origin_y + scale(top - origin_y);
+        Ok((
+            left,
+            top,
+            left + scale(right - left),
+            top + scale(bottom - top),
+        ))
+    }
+
+    /// Returns the raw `DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY` value without depending on the
+    /// `windows` type, for persisting and comparing.\
+    /// When the output technology is unknown, `DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER` (-1) is returned.
+    pub fn output_technology_raw(&self) -> i32 {
+        self.output_technology
+            .unwrap_or(DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER)
+            .0
+    }
+
+    /// Returns the parsed connector type of this monitor.
+    pub fn connector_type(&self) -> ConnectorType {
+        self.output_technology
+            .unwrap_or(DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER)
+            .into()
+    }
+
+    /// Returns the edge of the desktop the taskbar is docked to, determined by which edge of
+    /// `size` is not covered by `work_area_size`.\
+    /// A `None` value means the taskbar is not on this monitor or is set to auto-hide.
+    pub fn taskbar_edge(&self) -> Option<Edge> {
+        if self.work_area_size.top > self.size.top {
+            Some(Edge::Top)
+        } else if self.work_area_size.bottom < self.size.bottom {
+            Some(Edge::Bottom)
+        } else if self.work_area_size.left > self.size.left {
+            Some(Edge::Left)
+        } else if self.work_area_size.right < self.size.right {
+            Some(Edge::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the edge of the physical panel the taskbar is on, taking the monitor's
+    /// `orientation` into account (e.g. a "bottom" taskbar on a `Portrait` monitor is physically
+    /// on the left edge of the panel).
+    pub fn oriented_taskbar_edge(&self) -> Option<Edge> {
+        self.taskbar_edge()
+            .map(|edge| edge.rotated(self.orientation))
+    }
+
+    /// Returns the full EDID of the monitor (the base block followed by every extension block) as
+    /// read from the registry, for parsing it with another EDID parser (e.g. for DisplayID).\
+    /// A `None` value means the monitor does not publish an EDID (e.g. virtual displays and some
+    /// internal panels).
+    pub fn raw_edid(&self) -> Result<Option<Vec<u8>>, Error> {
+        Ok(unsafe { edid::read_edid(&self.device_path) }?)
+    }
+
+    /// Returns the raw bytes of every EDID extension block (e.g. CTA-861) following the base
+    /// 128 byte block.\
+    /// An empty `Vec` is returned if the monitor has no EDID or no extension blocks.
+    pub fn read_edid_extensions(&self) -> Result<Vec<u8>, Error> {
+        let edid = unsafe { edid::read_edid(&self.device_path) }?;
+        Ok(edid
+            .map(|edid| edid::extension_blocks(&edid).to_vec())
+            .unwrap_or_default())
+    }
+
+    /// Returns the HDR static metadata (including the declared peak luminance) from the CTA-861
+    /// extension blocks, or `None` if the monitor does not declare any.
+    pub fn hdr_static_metadata(&self) -> Result<Option<HdrStaticMetadata>, Error> {
+        Ok(edid::parse_hdr_static_metadata(
+            &self.read_edid_extensions()?,
+        ))
+    }
+
+    /// Returns an identifier for persisting per-monitor settings, built from the EDID manufacturer
+    /// ID, product code and serial number, e.g. `DEL-A0C2-7MT0187Q0N1L`.\
+    /// This stays the same across reboots and port changes, and differs between two monitors of
+    /// the same model as long as they have serial numbers. Monitors without a serial number fall
+    /// back to a hash of the `device_path` in place of it, and monitors without an EDID to
+    /// `PATH-` followed by that hash, both of which change when the monitor is moved to another
+    /// port.
+    pub fn stable_id(&self) -> String {
+        let path_hash = || {
+            format!(
+                "{:016X}",
+                fnv1a_64(self.device_path.to_lowercase().as_bytes())
+            )
+        };
+
+        let Some(edid) = &self.edid else {
+            return format!("PATH-{}", path_hash());
+        };
+
+        let serial = edid
+            .serial_string
+            .as_deref()
+            .map(|serial| serial.chars().filter(char::is_ascii_alphanumeric).collect())
+            .filter(|serial: &String| !serial.is_empty())
+            .or_else(|| edid.serial_number.map(|serial| format!("{serial:08X}")))
+            .unwrap_or_else(path_hash);
+
+        format!(
+            "{}-{:04X}-{serial}",
+            edid.manufacturer_id, edid.product_code
+        )
+    }
+
+    /// Returns the container ID (`DEVPKEY_Device_ContainerId`) of this monitor's device node,
+    /// formatted as `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`.\
+    /// Windows assigns the same container ID to every function of a physical device, so this can
+    /// be used to group a monitor with its audio endpoints or USB hub.\
+    /// A `None` value means the device node or its container ID could not be found.
+    pub fn container_id(&self) -> Result<Option<String>, Error> {
+        let container_id = unsafe { container_id_for_device_path(&self.device_path) }?;
+        Ok(container_id.map(|id| format!("{{{id:?}}}")))
+    }
+
+    /// Returns the supported vertical, horizontal and pixel clock ranges from the EDID Display
+    /// Range Limits descriptor.\
+    /// A `None` value means the monitor has no EDID or does not declare its range limits.
+    pub fn frequency_ranges(&self) -> Result<Option<FrequencyRanges>, Error> {
+        let edid = unsafe { edid::read_edid(&self.device_path) }?;
+        Ok(edid.as_deref().and_then(edid::parse_frequency_ranges))
+    }
+
+    /// Returns every ASCII text descriptor in the base EDID block (serial number, data strings and
+    /// product name), in the order they appear, which some vendors use for model variant or asset
+    /// information.
+    pub fn edid_text_descriptors(&self) -> Result<Vec<String>, Error> {
+        let edid = unsafe { edid::read_edid(&self.device_path) }?;
+        Ok(edid
+            .map(|edid| edid::parse_text_descriptors(&edid))
+            .unwrap_or_default())
+    }
+
+    /// Returns the audio formats (codecs, channels, sample rates) declared in the Short Audio
+    /// Descriptors of the CTA-861 extension blocks.\
+    /// The result is empty when the monitor declares no audio support.
+    pub fn audio_formats(&self) -> Result<Vec<AudioFormat>, Error> {
+        Ok(edid::parse_audio_formats(&self.read_edid_extensions()?))
+    }
+
+    /// Returns the luminance range reported by the DXGI output driving this monitor.\
+    /// A `None` value means the monitor is not currently in HDR mode (or has no DXGI output).
+    pub fn luminance_range(&self) -> Result<Option<LuminanceRange>, Error> {
+        let desc = unsafe { dxgi::output_desc_for_gdi_name(gdi_device_name(&self.device_name)) }?;
+        Ok(desc.as_ref().and_then(LuminanceRange::from_output_desc))
+    }
+
+    /// Returns the region of the target that the desktop image occupies as `(left, top, right,
+    /// bottom)`, from the path's `DISPLAYCONFIG_DESKTOP_IMAGE_INFO`.\
+    /// This differs from the full target when the desktop is scaled, e.g. to compensate for TV
+    /// overscan.\
+    /// A `None` value means the path has no desktop image info (or is no longer active).\
+    /// Note: This requires Windows 10 1803 or later.
+    pub fn desktop_image_region(&self) -> Result<Option<(i32, i32, i32, i32)>, Error> {
+        let info = unsafe { get_desktop_image_info(&self.device_path) }?;
+        Ok(info.map(|info| {
+            let r = info.DesktopImageRegion;
+            (r.left, r.top, r.right, r.bottom)
+        }))
+    }
+
+    /// Returns the video signal actually sent to the monitor, from the target mode of its
+    /// `DISPLAYCONFIG` path.\
+    /// This differs from the desktop resolution when the GPU scales the desktop (e.g. a 1080p
+    /// desktop sent as a 4K signal).\
+    /// A `None` value means the device has no active path.
+    pub fn signal_mode(&self) -> Result<Option<SignalMode>, Error> {
+        let info = unsafe { get_target_signal_info(&self.device_path) }?;
+        Ok(info.map(|info| {
+            let v_sync = info.vSyncFreq;
+            SignalMode {
+                active: (info.activeSize.cx, info.activeSize.cy),
+                total: (info.totalSize.cx, info.totalSize.cy),
+                pixel_rate_hz: info.pixelRate,
+                v_sync: if v_sync.Denominator == 0 {
+                    0.0
+                } else {
+                    f64::from(v_sync.Numerator) / f64::from(v_sync.Denominator)
+                },
+            }
+        }))
+    }
+
+    /// Whether this is a built-in panel, e.g. of a laptop, i.e. its `ConnectorType` is `Internal`.
+    /// Devices with an unknown output technology are not internal.
+    pub fn is_internal(&self) -> bool {
+        self.connector_type() == ConnectorType::Internal
+    }
+
+    /// Checks whether a rectangle in virtual desktop coordinates (e.g. a window's rectangle) lies
+    /// entirely within the bounds of this monitor (`size`).
+    pub fn contains_rect(&self, rect: RECT) -> bool {
+        rect.left >= self.size.left
+            && rect.top >= self.size.top
+            && rect.right <= self.size.right
+            && rect.bottom <= self.size.bottom
+    }
+
+    /// Returns the area in pixels of the overlap between a rectangle in virtual desktop
+    /// coordinates and the bounds of this monitor (`size`), or 0 if they don't intersect.\
+    /// The monitor a window mostly overlaps is the one with the largest intersection area, as
+    /// returned first by `displays_under_window`.
+    pub fn intersection_area(&self, rect: RECT) -> i64 {
+        intersection_area(self.size, rect)
+    }
+
+    /// Returns the device after this one in `order` (e.g. from `displays_in_navigation_order`),
+    /// wrapping around at the end.\
+    /// A `None` value means this device is not in `order`.
+    pub fn next_in<'a>(&self, order: &'a [Device]) -> Option<&'a Device> {
+        let idx = self.position_in(order)?;
+        order.get((idx + 1) % order.len())
+    }
+
+    /// Returns the device before this one in `order` (e.g. from `displays_in_navigation_order`),
+    /// wrapping around at the start.\
+    /// A `None` value means this device is not in `order`.
+    pub fn prev_in<'a>(&self, order: &'a [Device]) -> Option<&'a Device> {
+        let idx = self.position_in(order)?;
+        order.get((idx + order.len() - 1) % order.len())
+    }
+
+    fn position_in(&self, order: &[Device]) -> Option<usize> {
+        order
+            .iter()
+            .position(|d| d.hmonitor == self.hmonitor && d.device_path == self.device_path)
+    }
+
+    /// Returns whether Windows is hiding modes the monitor supports, from the
+    /// `DISPLAY_DEVICE_MODESPRUNED` state flag of the display adapter output driving it (this is
+    /// common after driver issues).\
+    /// When this is true, a resolution picker should offer a "show all modes" option, because the
+    /// monitor's native resolution may be missing from the pruned list.
+    pub fn modes_pruned(&self) -> bool {
+        let name = gdi_device_name(&self.device_name);
+        unsafe { enum_display_devices(PCWSTR::null()) }
+            .iter()
+            .find(|adapter| wchar_to_string(&adapter.DeviceName) == name)
+            .is_some_and(|adapter| flag_set(adapter.StateFlags, DISPLAY_DEVICE_MODESPRUNED))
+    }
+
+    /// Checks whether the display supports a resolution, and optionally a refresh rate, by looking
+    /// through the modes reported by `EnumDisplaySettingsExW`.\
+    /// Refresh rates are matched within 1 Hz, as fractional rates such as 59.94 Hz are reported as
+    /// either 59 or 60 Hz depending on the driver.\
+    /// A display whose modes can't be enumerated (e.g. one which was just disconnected) supports
+    /// none of them.
+    pub fn supports_mode(&self, width: u32, height: u32, refresh_hz: Option<u32>) -> bool {
+        let name = gdi_device_name(&self.device_name)
+            .encode_utf16()
+            .chain(once(0))
+            .collect::<Vec<_>>();
+
+        unsafe { get_display_modes(&name) }.iter().any(|mode| {
+            mode.dmPelsWidth == width
+                && mode.dmPelsHeight == height
+                && match refresh_hz {
+                    Some(hz) => mode.dmDisplayFrequency.abs_diff(hz) <= 1,
+                    None => true,
+                }
+        })
+    }
+
+    /// Reads the current color temperature of this monitor in Kelvin over DDC/CI, from the user
+    /// color temperature (VCP `0x0C`) or the selected color preset (VCP `0x14`).\
+    /// A `None` value means the monitor doesn't report a color temperature, or doesn't support
+    /// DDC/CI at all (e.g. most internal panels).
+    pub fn color_temperature(&self) -> Result<Option<u32>, Error> {
+        unsafe {
+            let Some((physical_monitor, _)) =
+                physical_monitor_for_path(HMONITOR::from(self.hmonitor), &self.device_path)?
+            else {
+                return Ok(None);
+            };
+            Ok(vcp::get_color_temperature(physical_monitor.0)?)
+        }
+    }
+}
+
+/// A `HMONITOR` handle, kept as its raw value so that devices stay `Send`.\
+/// The handle is only valid for the current session, and becomes stale when the monitor is
+/// disconnected or the display configuration changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MonitorHandle(isize);
+
+impl MonitorHandle {
+    /// Wraps a raw `HMONITOR` value, e.g. one received from another Win32 API
+    pub const fn new(raw: isize) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw `HMONITOR` value, for calling the Win32 API directly
+    pub const fn as_raw(self) -> isize {
+        self.0
+    }
+}
+
+impl From<HMONITOR> for MonitorHandle {
+    fn from(hmonitor: HMONITOR) -> Self {
+        Self(hmonitor.0 as isize)
+    }
+}
+
+impl From<MonitorHandle> for HMONITOR {
+    fn from(handle: MonitorHandle) -> Self {
+        HMONITOR(handle.0 as _)
     }
 }
 
@@ -102,6 +1113,18 @@ impl std::fmt::Debug for WrappedPhysicalMonitor {
     }
 }
 
+impl WrappedPhysicalMonitor {
+    /// Returns the raw physical monitor handle, e.g. for DDC/CI functions this crate doesn't wrap.\
+    /// The handle is still owned by this wrapper and destroyed when it is dropped, so it must not
+    /// be passed to `DestroyPhysicalMonitor` or used after this wrapper is dropped.
+    pub fn as_raw_handle(&self) -> HANDLE {
+        self.0
+    }
+}
+
+// Physical monitor handles are not tied to the thread which opened them
+unsafe impl Send for WrappedPhysicalMonitor {}
+
 impl Drop for WrappedPhysicalMonitor {
     fn drop(&mut self) {
         unsafe {
@@ -119,6 +1142,18 @@ impl std::fmt::Debug for WrappedFileHandle {
     }
 }
 
+impl WrappedFileHandle {
+    /// Returns the raw file handle of the monitor's device interface, e.g. for `DeviceIoControl`.\
+    /// The handle is still owned by this wrapper and closed when it is dropped, so it must not be
+    /// passed to `CloseHandle` or used after this wrapper is dropped.
+    pub fn as_raw_handle(&self) -> HANDLE {
+        self.0
+    }
+}
+
+// File handles belong to the process, so they can be used and closed from any thread
+unsafe impl Send for WrappedFileHandle {}
+
 impl Drop for WrappedFileHandle {
     fn drop(&mut self) {
         unsafe {
@@ -127,120 +1162,785 @@ impl Drop for WrappedFileHandle {
     }
 }
 
+/// Returns the area of the overlap between two rectangles, or 0 if they don't intersect
+fn intersection_area(a: RECT, b: RECT) -> i64 {
+    let width = i64::from(a.right.min(b.right)) - i64::from(a.left.max(b.left));
+    let height = i64::from(a.bottom.min(b.bottom)) - i64::from(a.top.max(b.top));
+    width.max(0) * height.max(0)
+}
+
 #[inline]
 fn flag_set<T: std::ops::BitAnd<Output = T> + PartialEq + Copy>(t: T, flag: T) -> bool {
     t & flag == flag
 }
 
-pub fn connected_displays_all() -> impl Iterator<Item = Result<Device, SysError>> {
-    unsafe {
-        let device_info_map = get_device_info_map().unwrap_or_else(|_| HashMap::new());
+pub fn connected_displays_all() -> impl Iterator<Item = Result<Device, SysError>> + Send {
+    connected_displays_all_from(unsafe { get_device_info_map() }.unwrap_or_default())
+}
 
+/// Enumerates devices using an existing `DeviceInfoMap`, e.g. one cached by a `DisplayCache`
+pub(crate) fn connected_displays_all_from(
+    device_info_map: DeviceInfoMap,
+) -> impl Iterator<Item = Result<Device, SysError>> + Send {
+    unsafe {
+        // HMONITOR wraps a raw pointer, so the handles are carried as MonitorHandle to keep the
+        // returned iterator Send
         let hmonitors = match enum_display_monitors() {
-            Ok(monitors) => monitors,
+            Ok(monitors) => monitors
+                .into_iter()
+                .map(MonitorHandle::from)
+                .collect::<Vec<_>>(),
             Err(e) => return Either::Right(once(Err(e))),
         };
 
-        Either::Left(
-            hmonitors
-                .into_iter()
-                .enumerate()
-                .flat_map(move |(idx, hmonitor)| {
-                    let mut display_devices =
-                        match get_display_devices_from_hmonitor(hmonitor) {
-                            Ok(p) => p,
-                            Err(e) => return vec![Err(e)],
-                        };
+        Either::Left(
+            hmonitors
+                .into_iter()
+                .enumerate()
+                .flat_map(move |(idx, hmonitor)| {
+                    get_devices_from_hmonitor(idx, hmonitor.into(), &device_info_map)
+                }),
+        )
+    }
+}
+
+/// The delay before the second attempt of `connected_displays_all_retry`, which grows linearly with
+/// every further attempt
+const RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+pub fn connected_displays_all_retry(max_attempts: usize) -> Result<Vec<Device>, SysError> {
+    let mut attempt = 1;
+    loop {
+        match connected_displays_all().collect::<Result<Vec<_>, _>>() {
+            Err(e) if e.is_enumeration_race() && attempt < max_attempts => {
+                thread::sleep(RETRY_BACKOFF * attempt as u32);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+pub fn connected_displays_all_lossy() -> (Vec<Device>, Vec<SysError>) {
+    connected_displays_all().partition_map(|device| match device {
+        Ok(device) => Either::Left(device),
+        Err(e) => Either::Right(e),
+    })
+}
+
+pub fn connected_displays_all_including_inactive() -> Result<Vec<Device>, SysError> {
+    // Like the lossy enumeration, a device which fails to enumerate only drops that device, unless
+    // it leaves nothing to return
+    let (mut devices, errors) = connected_displays_all_lossy();
+    if let (true, Some(e)) = (devices.is_empty(), errors.into_iter().next()) {
+        return Err(e);
+    }
+
+    unsafe {
+        for adapter in enum_display_devices(PCWSTR::null()) {
+            for monitor in enum_display_devices(PCWSTR(adapter.DeviceName.as_ptr())) {
+                // Display devices which are neither active nor attached are leftovers of monitors
+                // which were connected to this output before
+                if !flag_set(monitor.StateFlags, DISPLAY_DEVICE_ATTACHED)
+                    || flag_set(monitor.StateFlags, DISPLAY_DEVICE_ACTIVE)
+                {
+                    continue;
+                }
+
+                let device_path = wchar_to_string(&monitor.DeviceID);
+                if devices
+                    .iter()
+                    .any(|device| device.device_path == device_path)
+                {
+                    continue;
+                }
+
+                let raw_edid = edid::read_edid(&device_path).ok().flatten();
+                devices.push(Device {
+                    hmonitor: MonitorHandle::default(),
+                    size: RECT::default(),
+                    work_area_size: RECT::default(),
+                    device_name: wchar_to_string(&monitor.DeviceName),
+                    device_description: description_to_string(&monitor.DeviceString),
+                    friendly_name: description_to_string(&monitor.DeviceString),
+                    device_key: wchar_to_string(&monitor.DeviceKey),
+                    device_path,
+                    output_technology: None,
+                    orientation: Orientation::default(),
+                    adapter_device_path: None,
+                    adapter_name: description_to_string(&adapter.DeviceString),
+                    adapter_id: None,
+                    target_id: None,
+                    hdr_enabled: false,
+                    sdr_white_level_nits: None,
+                    bits_per_color_channel: DEFAULT_BITS_PER_COLOR_CHANNEL,
+                    refresh_rate_hz: 0,
+                    edid: raw_edid.as_deref().and_then(edid::parse_edid_info),
+                    physical_size_mm: raw_edid.as_deref().and_then(edid::parse_physical_size_mm),
+                    resolution: (0, 0),
+                    dpi: USER_DEFAULT_SCREEN_DPI,
+                    scale_factor: 1.0,
+                    is_primary: false,
+                    is_duplicated: false,
+                    is_active: false,
+                    is_attached: true,
+                });
+            }
+        }
+    }
+
+    Ok(devices)
+}
+
+/// Returns every display the window's rectangle intersects, ordered by intersection area
+/// (largest first).
+pub fn displays_under_window(hwnd: isize) -> Result<Vec<Device>, SysError> {
+    let mut rect = RECT::default();
+    unsafe { GetWindowRect(HWND(hwnd as _), &mut rect) }.map_err(SysError::GetWindowRectFailed)?;
+
+    let mut displays = connected_displays_all()
+        .map(|device| device.map(|device| (device.intersection_area(rect), device)))
+        .filter(|device| !matches!(device, Ok((0, _))))
+        .collect::<Result<Vec<_>, _>>()?;
+    displays.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    Ok(displays.into_iter().map(|(_, device)| device).collect())
+}
+
+pub fn displays_intersecting(rect: RECT) -> Result<Vec<Device>, SysError> {
+    unsafe {
+        // The index of each HMONITOR among all of them is needed by the lenient lookup, so every
+        // HMONITOR is enumerated and clipped here rather than by EnumDisplayMonitors
+        let intersecting = enum_display_monitor_rects()?
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (_, monitor_rect))| intersection_area(*monitor_rect, rect) > 0)
+            .collect::<Vec<_>>();
+        if intersecting.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let device_info_map = get_device_info_map().unwrap_or_default();
+        intersecting
+            .into_iter()
+            .flat_map(|(idx, (hmonitor, _))| {
+                get_devices_from_hmonitor(idx, hmonitor, &device_info_map)
+            })
+            .collect()
+    }
+}
+
+pub fn device_from_point(x: i32, y: i32) -> Result<Option<Device>, SysError> {
+    unsafe {
+        let hmonitor = MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONULL);
+        if hmonitor.is_invalid() {
+            return Ok(None);
+        }
+        device_from_hmonitor(hmonitor)
+    }
+}
+
+pub fn nearest_display(rect: RECT) -> Result<Device, SysError> {
+    // Doubled centers keep the arithmetic in integers
+    let center = |r: RECT| {
+        (
+            i64::from(r.left) + i64::from(r.right),
+            i64::from(r.top) + i64::from(r.bottom),
+        )
+    };
+    let (x, y) = center(rect);
+
+    connected_displays_all()
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .min_by_key(|device| {
+            let (dx, dy) = center(device.size);
+            (
+                Reverse(device.intersection_area(rect)),
+                (dx - x).pow(2) + (dy - y).pow(2),
+            )
+        })
+        .ok_or(SysError::NoDisplays)
+}
+
+pub fn internal_display() -> Result<Option<Device>, SysError> {
+    for device in connected_displays_all() {
+        let device = device?;
+        if device.is_internal() {
+            return Ok(Some(device));
+        }
+    }
+    Ok(None)
+}
+
+pub fn external_displays() -> Result<Vec<Device>, SysError> {
+    let filter = DeviceFilter {
+        external_only: true,
+        ..DeviceFilter::default()
+    };
+    connected_displays_all()
+        .filter(|device| {
+            device
+                .as_ref()
+                .map_or(true, |device| filter.matches_device(device))
+        })
+        .collect()
+}
+
+pub fn connected_displays_all_sorted() -> Result<Vec<Device>, SysError> {
+    let mut displays = connected_displays_all().collect::<Result<Vec<_>, _>>()?;
+    // The Device Path breaks ties between devices in "Duplicate" mode, which share a position
+    displays.sort_by_cached_key(|device| {
+        (
+            !device.is_primary,
+            device.size.left,
+            device.size.top,
+            device.device_path.clone(),
+        )
+    });
+    Ok(displays)
+}
+
+pub fn displays_in_navigation_order() -> Result<Vec<Device>, SysError> {
+    let mut displays = connected_displays_all().collect::<Result<Vec<_>, _>>()?;
+    displays.sort_by_key(|device| (device.size.left, device.size.top));
+    Ok(displays)
+}
+
+pub fn layout() -> Result<Vec<(Device, LayoutCell)>, SysError> {
+    let displays = connected_displays_all().collect::<Result<Vec<_>, _>>()?;
+    let cols = layout_ranks(&displays, |r| (r.left, r.right));
+    let rows = layout_ranks(&displays, |r| (r.top, r.bottom));
+
+    let origin = displays
+        .iter()
+        .position(|device| device.is_primary)
+        .map_or((0, 0), |idx| (cols[idx], rows[idx]));
+
+    let mut layout = displays
+        .into_iter()
+        .zip(cols.into_iter().zip(rows))
+        .map(|(device, (col, row))| {
+            let cell = LayoutCell {
+                col: col - origin.0,
+                row: row - origin.1,
+            };
+            (device, cell)
+        })
+        .collect::<Vec<_>>();
+    layout.sort_by_key(|(device, cell)| (cell.row, cell.col, device.size.left, device.size.top));
+
+    Ok(layout)
+}
+
+/// Ranks every device along one axis by the length of the longest chain of devices which lie
+/// entirely before it (touching edges count), given the `(start, end)` of a rectangle on that
+/// axis.\
+/// Unlike ranking by the raw coordinate, monitors of different sizes which are merely offset
+/// (e.g. bottom-aligned side by side) get different columns but the same row, and monitors which
+/// overlap on the axis always get the same rank.
+fn layout_ranks(displays: &[Device], span: impl Fn(RECT) -> (i32, i32)) -> Vec<i32> {
+    let mut order = (0..displays.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&idx| span(displays[idx].size).0);
+
+    let mut ranks = vec![0; displays.len()];
+    for (position, &idx) in order.iter().enumerate() {
+        let (start, _) = span(displays[idx].size);
+        ranks[idx] = order[..position]
+            .iter()
+            .filter(|&&before| span(displays[before].size).1 <= start)
+            .map(|&before| ranks[before] + 1)
+            .max()
+            .unwrap_or(0);
+    }
+
+    ranks
+}
+
+/// Reads the bounding rectangle of every monitor from the `SM_*VIRTUALSCREEN` system metrics
+pub fn virtual_screen_bounds() -> Result<RECT, SysError> {
+    unsafe {
+        let (left, top) = (
+            GetSystemMetrics(SM_XVIRTUALSCREEN),
+            GetSystemMetrics(SM_YVIRTUALSCREEN),
+        );
+        let (width, height) = (
+            GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        );
+
+        // The metrics are 0 when they can't be read, e.g. from a session without a desktop
+        if width == 0 || height == 0 {
+            return Err(SysError::NoDisplays);
+        }
+
+        Ok(RECT {
+            left,
+            top,
+            right: left + width,
+            bottom: top + height,
+        })
+    }
+}
+
+/// Returns the total number of usable desktop pixels, i.e. the sum of every monitor's work area.\
+/// Display devices sharing a `HMONITOR` (e.g. "Duplicate" mode) are only counted once.
+pub fn total_work_area_pixels() -> Result<i64, SysError> {
+    let mut seen = HashSet::new();
+    connected_displays_all().try_fold(0, |total, device| {
+        let device = device?;
+        if !seen.insert(device.hmonitor) {
+            return Ok(total);
+        }
+        let work_area = device.work_area_size;
+        let width = i64::from(work_area.right) - i64::from(work_area.left);
+        let height = i64::from(work_area.bottom) - i64::from(work_area.top);
+        Ok(total + width * height)
+    })
+}
+
+pub fn connected_displays_deduplicated() -> Result<Vec<Device>, SysError> {
+    /// Lower is preferred: real outputs, then unknown ones, then virtual (e.g. RDP)
+    fn rank(device: &Device) -> u8 {
+        match device.output_technology {
+            Some(DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_VIRTUAL) => 2,
+            Some(DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER) | None => 1,
+            Some(_) => 0,
+        }
+    }
+
+    // Mirror drivers (e.g. of remote control software) show up as adapters of their own which copy
+    // the desktop of a real one, so they are never a monitor a human would count
+    let mirroring_adapters = unsafe { enum_display_devices(PCWSTR::null()) }
+        .into_iter()
+        .filter(|adapter| flag_set(adapter.StateFlags, DISPLAY_DEVICE_MIRRORING_DRIVER))
+        .map(|adapter| wchar_to_string(&adapter.DeviceName))
+        .collect::<Vec<_>>();
+
+    let mut devices: Vec<Device> = Vec::new();
+    for device in connected_displays_all() {
+        let device = device?;
+        if mirroring_adapters
+            .iter()
+            .any(|adapter| adapter == gdi_device_name(&device.device_name))
+        {
+            continue;
+        }
+
+        let existing = devices.iter_mut().find(|existing| {
+            existing.size == device.size
+                || (!device.device_path.is_empty() && existing.device_path == device.device_path)
+        });
+
+        match existing {
+            Some(existing) if rank(&device) < rank(existing) => *existing = device,
+            Some(_) => {}
+            None => devices.push(device),
+        }
+    }
+
+    Ok(devices)
+}
+
+pub fn connected_displays_by_path() -> Result<HashMap<String, Device>, SysError> {
+    let mut devices = HashMap::new();
+    for device in connected_displays_all() {
+        let device = device?;
+        devices.entry(device.device_path.clone()).or_insert(device);
+    }
+    Ok(devices)
+}
+
+/// Groups the devices which share an `HMONITOR` because they are in "Duplicate" mode.\
+/// The primary member is the device whose path comes first in `QueryDisplayConfig`, which
+/// returns paths in priority order: that is the target whose mode the clone is driven with.
+pub fn duplicate_groups() -> Result<Vec<DuplicateGroup>, SysError> {
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    for device in connected_displays_all() {
+        let device = device?;
+        match groups
+            .iter_mut()
+            .find(|group| group.devices[0].hmonitor == device.hmonitor)
+        {
+            Some(group) => group.devices.push(device),
+            None => groups.push(DuplicateGroup {
+                devices: vec![device],
+                primary: 0,
+            }),
+        }
+    }
+    groups.retain(|group| group.devices.len() > 1);
+
+    if !groups.is_empty() {
+        let priority = unsafe { active_target_device_paths() }?;
+        for group in &mut groups {
+            group.primary = priority
+                .iter()
+                .find_map(|path| group.devices.iter().position(|d| &d.device_path == path))
+                .unwrap_or_default();
+        }
+    }
+
+    Ok(groups)
+}
+
+pub fn grouped_displays() -> Result<Vec<MonitorGroup>, SysError> {
+    let mut groups: Vec<MonitorGroup> = Vec::new();
+    for device in connected_displays_physical() {
+        let device = device?;
+        match groups
+            .iter_mut()
+            .find(|group| group.hmonitor == device.hmonitor)
+        {
+            Some(group) => group.devices.push(device),
+            None => groups.push(MonitorGroup {
+                hmonitor: device.hmonitor,
+                devices: vec![device],
+            }),
+        }
+    }
+
+    Ok(groups)
+}
+
+/// Cheaply checks whether a `HMONITOR` still refers to a monitor, by calling `GetMonitorInfoW`.
+pub fn is_hmonitor_valid(hmonitor: MonitorHandle) -> bool {
+    unsafe { get_monitor_info(hmonitor.into()).is_ok() }
+}
+
+/// Looks up the `Device` for a single `HMONITOR`.\
+/// A `None` value means the `HMONITOR` is not (or is no longer) attached to the desktop.\
+/// When multiple display devices share the `HMONITOR` (e.g. "Duplicate" mode) the first is returned.
+pub(crate) unsafe fn device_from_hmonitor(hmonitor: HMONITOR) -> Result<Option<Device>, SysError> {
+    // Stale handles are rejected cheaply before enumerating every monitor
+    if get_monitor_info(hmonitor).is_err() {
+        return Ok(None);
+    }
+
+    let Some(idx) = enum_display_monitors()?
+        .into_iter()
+        .position(|h| h == hmonitor)
+    else {
+        return Ok(None);
+    };
+
+    let device_info_map = get_device_info_map().unwrap_or_default();
+    get_devices_from_hmonitor(idx, hmonitor, &device_info_map)
+        .into_iter()
+        .next()
+        .transpose()
+}
+
+/// Builds every `Device` belonging to a `HMONITOR`, falling back to the lenient lookup for
+/// virtualized monitors which have no active display devices.\
+/// `idx` is the position of the `HMONITOR` in the `EnumDisplayMonitors` results.
+unsafe fn get_devices_from_hmonitor(
+    idx: usize,
+    hmonitor: HMONITOR,
+    device_info_map: &DeviceInfoMap,
+) -> Vec<Result<Device, SysError>> {
+    // Attached but inactive display devices are left to connected_displays_all_including_inactive
+    let mut display_devices = match get_active_display_devices_from_hmonitor(hmonitor) {
+        Ok(p) => p,
+        Err(e) => return vec![Err(e)],
+    };
+
+    let lenient = display_devices.is_empty();
+    if lenient {
+        display_devices =
+            match get_display_devices_from_hmonitor_lenient(idx, hmonitor, device_info_map) {
+                Ok(p) => p,
+                Err(e) => return vec![Err(e)],
+            };
+    }
+
+    let is_duplicated = display_devices.len() > 1;
+    display_devices
+        .into_iter()
+        .map(|(monitor_info, display_device)| {
+            let target_name = device_info_map.get(&display_device);
+            let output_technology = target_name.map(|d| d.outputTechnology);
+            let adapter_id =
+                target_name.map(|d| (d.header.adapterId.LowPart, d.header.adapterId.HighPart));
+            let target_id = target_name.map(|d| d.header.id);
+
+            // Systems without advanced color support fail these queries, which is reported as
+            // 8-bit SDR rather than failing the enumeration
+            let color_info = target_name
+                .and_then(|d| get_advanced_color_info(d.header.adapterId, d.header.id).ok());
+            let hdr_enabled = color_info
+                .is_some_and(|info| flag_set(info.Anonymous.value, ADVANCED_COLOR_ENABLED));
+            let bits_per_color_channel = color_info
+                .map(|info| info.bitsPerColorChannel)
+                .filter(|&bits| bits > 0)
+                .unwrap_or(DEFAULT_BITS_PER_COLOR_CHANNEL);
+            let sdr_white_level_nits = target_name
+                .and_then(|d| get_sdr_white_level_nits(d.header.adapterId, d.header.id).ok());
+            let adapter_device_path = target_name
+                .and_then(|d| device_info_map.adapter_device_path(d.header.adapterId))
+                .map(str::to_string);
+
+            let mode = get_current_display_settings(&monitor_info.szDevice).unwrap_or_default();
+            let (dpi, scale_factor) = get_scale_for_monitor(hmonitor);
+            let device_path = wchar_to_string(&display_device.DeviceID);
+            let raw_edid = edid::read_edid(&device_path).ok().flatten();
+            let edid = raw_edid.as_deref().and_then(edid::parse_edid_info);
+            let physical_size_mm = raw_edid.as_deref().and_then(edid::parse_physical_size_mm);
+
+            Ok(Device {
+                hmonitor: hmonitor.into(),
+                size: monitor_info.monitorInfo.rcMonitor,
+                work_area_size: monitor_info.monitorInfo.rcWork,
+                device_name: wchar_to_string(&display_device.DeviceName),
+                device_description: description_to_string(&display_device.DeviceString),
+                friendly_name: friendly_name(target_name, &display_device),
+                device_key: wchar_to_string(&display_device.DeviceKey),
+                device_path,
+                output_technology,
+                orientation: Orientation::from(mode.Anonymous1.Anonymous2.dmDisplayOrientation),
+                adapter_device_path,
+                adapter_name: device_info_map.adapter_name(&display_device),
+                adapter_id,
+                target_id,
+                hdr_enabled,
+                sdr_white_level_nits,
+                bits_per_color_channel,
+                refresh_rate_hz: mode.dmDisplayFrequency,
+                resolution: (mode.dmPelsWidth, mode.dmPelsHeight),
+                edid,
+                physical_size_mm,
+                dpi,
+                scale_factor,
+                is_primary: flag_set(monitor_info.monitorInfo.dwFlags, MONITORINFOF_PRIMARY),
+                is_duplicated,
+                is_active: true,
+                // The state flags of the lenient lookup are those of the adapter, but the monitor
+                // does have a HMONITOR so it is attached
+                is_attached: lenient
+                    || flag_set(display_device.StateFlags, DISPLAY_DEVICE_ATTACHED),
+            })
+        })
+        .collect()
+}
+
+pub fn connected_displays_physical() -> impl Iterator<Item = Result<PhysicalDevice, SysError>> {
+    connected_displays_physical_with(false, DeviceFilter::default())
+}
+
+/// The criteria of a `DisplayQuery`, which are checked before the physical monitor and file
+/// handles of a device are opened
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DeviceFilter {
+    /// Skip built-in panels, i.e. devices whose `ConnectorType` is `Internal`
+    pub external_only: bool,
+    pub primary_only: bool,
+    /// The minimum `(width, height)` of the current display mode
+    pub min_resolution: Option<(u32, u32)>,
+}
+
+impl DeviceFilter {
+    /// Devices with an unknown output technology are considered external, and devices whose
+    /// display mode is unknown are kept so that the error is reported by the enumeration.
+    fn matches(
+        &self,
+        output_technology: Option<DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY>,
+        is_primary: bool,
+        resolution: Option<(u32, u32)>,
+    ) -> bool {
+        if self.external_only
+            && output_technology.is_some_and(|t| ConnectorType::from(t) == ConnectorType::Internal)
+        {
+            return false;
+        }
+
+        if self.primary_only && !is_primary {
+            return false;
+        }
+
+        match (self.min_resolution, resolution) {
+            (Some((min_width, min_height)), Some((width, height))) => {
+                width >= min_width && height >= min_height
+            }
+            _ => true,
+        }
+    }
 
-                    if display_devices.is_empty() {
-                        display_devices = match get_display_devices_from_hmonitor_lenient(idx, hmonitor) {
-                            Ok(p) => p,
-                            Err(e) => return vec![Err(e)],
-                        };
+    pub fn matches_device(&self, device: &Device) -> bool {
+        self.matches(
+            device.output_technology,
+            device.is_primary,
+            Some(device.resolution),
+        )
+    }
 
-                    }
-
-                    display_devices
-                        .into_iter()
-                        .map(|(monitor_info, display_device)| {
-                            let output_technology = device_info_map
-                                .get(&display_device.DeviceID)
-                                .map(|d| Some(d.outputTechnology))
-                                .unwrap_or(None);
-
-                            Ok(Device {
-                                hmonitor: hmonitor.0 as isize,
-                                size: monitor_info.monitorInfo.rcMonitor,
-                                work_area_size: monitor_info.monitorInfo.rcWork,
-                                device_name: wchar_to_string(&display_device.DeviceName),
-                                device_description: wchar_to_string(&display_device.DeviceString),
-                                device_key: wchar_to_string(&display_device.DeviceKey),
-                                device_path: wchar_to_string(&display_device.DeviceID),
-                                output_technology,
-                            })
-                        })
-                        .collect()
-                }),
+    unsafe fn matches_display_device(
+        &self,
+        monitor_info: &MONITORINFOEXW,
+        display_device: &DISPLAY_DEVICEW,
+        device_info_map: &DeviceInfoMap,
+    ) -> bool {
+        // The display mode is only read when it is needed
+        let resolution = self.min_resolution.and_then(|_| {
+            get_current_display_settings(&monitor_info.szDevice)
+                .ok()
+                .map(|mode| (mode.dmPelsWidth, mode.dmPelsHeight))
+        });
+
+        self.matches(
+            device_info_map
+                .get(display_device)
+                .map(|info| info.outputTechnology),
+            flag_set(monitor_info.monitorInfo.dwFlags, MONITORINFOF_PRIMARY),
+            resolution,
         )
     }
 }
 
-pub fn connected_displays_physical() -> impl Iterator<Item = Result<PhysicalDevice, SysError>> {
-    unsafe {
-        let device_info_map = match get_device_info_map() {
-            Ok(info) => info,
-            Err(e) => return Either::Right(once(Err(e))),
-        };
+/// Enumerates physical devices.\
+/// When `lenient` is set, missing `DISPLAYCONFIG` data (e.g. access denied in restricted or remote
+/// sessions) is filled with `DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER` instead of producing
+/// `DeviceInfoMissing` errors.\
+/// Devices which don't match `filter` are skipped without opening their file handle, and an
+/// `HMONITOR` without any matching devices is skipped without opening its physical monitors.
+pub(crate) fn connected_displays_physical_with(
+    lenient: bool,
+    filter: DeviceFilter,
+) -> impl Iterator<Item = Result<PhysicalDevice, SysError>> {
+    let device_info_map = match unsafe { get_device_info_map() } {
+        Ok(info) => info,
+        Err(_) if lenient => DeviceInfoMap::default(),
+        Err(e) => return Either::Right(once(Err(e))),
+    };
+
+    Either::Left(connected_displays_physical_from(
+        device_info_map,
+        lenient,
+        filter,
+    ))
+}
 
+/// Like `connected_displays_physical_with`, using an existing `DeviceInfoMap`
+pub(crate) fn connected_displays_physical_from(
+    device_info_map: DeviceInfoMap,
+    lenient: bool,
+    filter: DeviceFilter,
+) -> impl Iterator<Item = Result<PhysicalDevice, SysError>> {
+    unsafe {
         let hmonitors = match enum_display_monitors() {
             Ok(monitors) => monitors,
             Err(e) => return Either::Right(once(Err(e))),
         };
 
         Either::Left(hmonitors.into_iter().flat_map(move |hmonitor| {
-            let physical_monitors = match get_physical_monitors_from_hmonitor(hmonitor) {
+            let display_devices = match get_active_display_devices_from_hmonitor(hmonitor) {
                 Ok(p) => p,
                 Err(e) => return vec![Err(e)],
             };
 
-            let display_devices = match get_display_devices_from_hmonitor(hmonitor) {
+            let wanted = display_devices
+                .iter()
+                .map(|(monitor_info, display_device)| {
+                    filter.matches_display_device(monitor_info, display_device, &device_info_map)
+                })
+                .collect::<Vec<_>>();
+            if !wanted.contains(&true) {
+                return vec![];
+            }
+
+            let physical_monitors = match get_physical_monitors_from_hmonitor(hmonitor) {
                 Ok(p) => p,
+                // Some virtual monitors (e.g. DisplayLink/IddCx) fail with ERROR_GEN_FAILURE,
+                // skip just this HMONITOR rather than failing the whole enumeration
+                Err(SysError::GetPhysicalMonitorsFailed { source, .. })
+                    if source.code() == ERROR_GEN_FAILURE.to_hresult() =>
+                {
+                    return vec![]
+                }
                 Err(e) => return vec![Err(e)],
             };
 
-            if display_devices.len() != physical_monitors.len() {
-                // There doesn't seem to be any way to directly associate a physical monitor
-                // handle with the equivalent display device, other than by array indexing
-                // https://stackoverflow.com/questions/63095216/how-to-associate-physical-monitor-with-monitor-deviceid
-                return vec![Err(SysError::EnumerationMismatch)];
+            // There doesn't seem to be any way to directly associate a physical monitor handle
+            // with the equivalent display device, other than by array indexing
+            // https://stackoverflow.com/questions/63095216/how-to-associate-physical-monitor-with-monitor-deviceid
+            // so at least check that the display devices belong to the source they were read from
+            if display_devices.len() != physical_monitors.len()
+                || !display_devices
+                    .iter()
+                    .all(|(_, display_device)| device_info_map.source_matches(display_device))
+            {
+                return vec![Err(enumeration_mismatch(
+                    hmonitor,
+                    physical_monitors.len(),
+                    display_devices.len(),
+                ))];
             }
+            let is_duplicated = display_devices.len() > 1;
             physical_monitors
                 .into_iter()
                 .zip(display_devices)
-                .filter_map(|(physical_monitor, (monitor_info, display_device))| {
-                    get_file_handle_for_display_device(&display_device)
-                        .transpose()
-                        .map(|file_handle| {
-                            (monitor_info, physical_monitor, display_device, file_handle)
-                        })
-                })
+                .zip(wanted)
+                .filter_map(
+                    |((physical_monitor, (monitor_info, display_device)), wanted)| {
+                        if !wanted {
+                            return None;
+                        }
+                        get_file_handle_for_display_device(&display_device)
+                            .transpose()
+                            .map(|file_handle| {
+                                (monitor_info, physical_monitor, display_device, file_handle)
+                            })
+                    },
+                )
                 .map(
                     |(monitor_info, physical_monitor, display_device, file_handle)| {
                         let file_handle = file_handle?;
-                        let info = device_info_map
-                            .get(&display_device.DeviceID)
-                            .ok_or(SysError::DeviceInfoMissing)?;
+                        let (output_technology, adapter_id, target_id) = match device_info_map
+                            .get(&display_device)
+                        {
+                            Some(info) => (
+                                info.outputTechnology,
+                                (
+                                    info.header.adapterId.LowPart,
+                                    info.header.adapterId.HighPart,
+                                ),
+                                info.header.id,
+                            ),
+                            None if lenient => (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER, (0, 0), 0),
+                            None => return Err(SysError::DeviceInfoMissing),
+                        };
+                        let mode = get_current_display_settings(&monitor_info.szDevice)
+                            .unwrap_or_default();
+                        let (dpi, scale_factor) = get_scale_for_monitor(hmonitor);
                         Ok(PhysicalDevice {
-                            hmonitor: hmonitor.0 as isize,
+                            hmonitor: hmonitor.into(),
                             size: monitor_info.monitorInfo.rcMonitor,
                             work_area_size: monitor_info.monitorInfo.rcWork,
                             physical_monitor,
                             file_handle,
                             device_name: wchar_to_string(&display_device.DeviceName),
-                            device_description: wchar_to_string(&display_device.DeviceString),
+                            device_description: description_to_string(&display_device.DeviceString),
+                            friendly_name: friendly_name(
+                                device_info_map.get(&display_device),
+                                &display_device,
+                            ),
                             device_key: wchar_to_string(&display_device.DeviceKey),
                             device_path: wchar_to_string(&display_device.DeviceID),
-                            output_technology: info.outputTechnology,
+                            output_technology,
+                            orientation: Orientation::from(
+                                mode.Anonymous1.Anonymous2.dmDisplayOrientation,
+                            ),
+                            adapter_id,
+                            target_id,
+                            refresh_rate_hz: mode.dmDisplayFrequency,
+                            resolution: (mode.dmPelsWidth, mode.dmPelsHeight),
+                            dpi,
+                            scale_factor,
+                            is_primary: flag_set(
+                                monitor_info.monitorInfo.dwFlags,
+                                MONITORINFOF_PRIMARY,
+                            ),
+                            is_duplicated,
+                            ddcci_supported: OnceLock::new(),
                         })
                     },
                 )
@@ -249,20 +1949,204 @@ pub fn connected_displays_physical() -> impl Iterator<Item = Result<PhysicalDevi
     }
 }
 
-/// Returns a `HashMap` of Device Path to `DISPLAYCONFIG_TARGET_DEVICE_NAME`.\
+/// The `DISPLAYCONFIG_TARGET_DEVICE_NAME` of every active path, indexed both by the GDI device
+/// name of the path's source (e.g. `\\.\DISPLAY1`) and by the target's Device Path.\
 /// This can be used to find the `DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY` for a monitor.\
-/// The output technology is used to determine if a device is internal or external.
-unsafe fn get_device_info_map(
-) -> Result<HashMap<[u16; 128], DISPLAYCONFIG_TARGET_DEVICE_NAME>, SysError> {
+/// The output technology is used to determine if a device is internal or external.\
+/// The adapter device path and name of every adapter are also cached here, so that they are
+/// queried once per enumeration rather than once per monitor. Since the map is rebuilt for every
+/// enumeration, a failed query is retried on the next one.
+#[derive(Clone, Default)]
+pub(crate) struct DeviceInfoMap {
+    by_gdi_name: HashMap<String, Vec<DISPLAYCONFIG_TARGET_DEVICE_NAME>>,
+    by_path: HashMap<[u16; 128], DISPLAYCONFIG_TARGET_DEVICE_NAME>,
+    /// Keyed by the `(LowPart, HighPart)` of the adapter LUID
+    adapter_device_paths: HashMap<(u32, i32), Option<String>>,
+    /// The `DeviceString` of every adapter keyed by its GDI device name (e.g. `\\.\DISPLAY1`),
+    /// read on first use so that it is also available when `DISPLAYCONFIG` is not
+    adapter_names: OnceLock<HashMap<String, String>>,
+}
+
+impl DeviceInfoMap {
+    /// The number of `DISPLAYCONFIG` targets in the map
+    pub(crate) fn target_count(&self) -> usize {
+        self.by_path.len()
+    }
+
+    /// Finds the device info for a display device.\
+    /// The GDI name of the source is authoritative; the Device Path is only used to pick between
+    /// multiple targets sharing a source (e.g. "Duplicate" mode) or when the source is unknown.
+    fn get(&self, display_device: &DISPLAY_DEVICEW) -> Option<&DISPLAYCONFIG_TARGET_DEVICE_NAME> {
+        let device_name = wchar_to_string(&display_device.DeviceName);
+
+        match self
+            .by_gdi_name
+            .get(gdi_device_name(&device_name))
+            .map(Vec::as_slice)
+        {
+            Some([info]) => Some(info),
+            Some(infos) => infos
+                .iter()
+                .find(|info| info.monitorDevicePath == display_device.DeviceID),
+            None => self.by_path.get(&display_device.DeviceID),
+        }
+    }
+
+    /// Checks that the `DISPLAYCONFIG` source whose target has the Device Path of a display device
+    /// has the same GDI name as the display device, i.e. that it was paired with the right source.\
+    /// Display devices without an active `DISPLAYCONFIG` target can't be checked, and pass.
+    fn source_matches(&self, display_device: &DISPLAY_DEVICEW) -> bool {
+        let device_name = wchar_to_string(&display_device.DeviceName);
+        let mut sources = self
+            .by_gdi_name
+            .iter()
+            .filter(|(_, targets)| {
+                targets
+                    .iter()
+                    .any(|target| target.monitorDevicePath == display_device.DeviceID)
+            })
+            .map(|(gdi_name, _)| gdi_name.as_str())
+            .peekable();
+
+        sources.peek().is_none()
+            || sources.any(|gdi_name| gdi_name == gdi_device_name(&device_name))
+    }
+
+    /// Returns the cached device instance path of an adapter
+    fn adapter_device_path(&self, adapter_id: LUID) -> Option<&str> {
+        self.adapter_device_paths
+            .get(&(adapter_id.LowPart, adapter_id.HighPart))
+            .and_then(Option::as_deref)
+    }
+
+    /// Returns the name (`DeviceString`) of the adapter driving a display device, found by
+    /// matching the GDI device name of the display device (e.g. `\\.\DISPLAY1` of
+    /// `\\.\DISPLAY1\Monitor0`) against the adapters' `DeviceName`, or an empty string when no
+    /// adapter matches.
+    unsafe fn adapter_name(&self, display_device: &DISPLAY_DEVICEW) -> String {
+        let device_name = wchar_to_string(&display_device.DeviceName);
+
+        self.adapter_names
+            .get_or_init(|| {
+                enum_display_devices(PCWSTR::null())
+                    .iter()
+                    .map(|adapter| {
+                        (
+                            wchar_to_string(&adapter.DeviceName),
+                            description_to_string(&adapter.DeviceString),
+                        )
+                    })
+                    .collect()
+            })
+            .get(gdi_device_name(&device_name))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Builds a `DeviceInfoMap` from the active `DISPLAYCONFIG` paths, using the
+/// `DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME` query to correlate each target with a GDI name.
+pub(crate) unsafe fn get_device_info_map() -> Result<DeviceInfoMap, SysError> {
+    let (display_paths, _) = query_display_config(QDC_ONLY_ACTIVE_PATHS)?;
+
+    let mut map = DeviceInfoMap::default();
+    for path in display_paths {
+        let Some(target_name) =
+            get_target_device_name(path.targetInfo.adapterId, path.targetInfo.id)?
+        else {
+            continue;
+        };
+
+        if let Some(gdi_name) = get_source_gdi_name(path.sourceInfo.adapterId, path.sourceInfo.id)?
+        {
+            map.by_gdi_name
+                .entry(gdi_name)
+                .or_default()
+                .push(target_name);
+        }
+
+        let adapter_id = target_name.header.adapterId;
+        map.adapter_device_paths
+            .entry((adapter_id.LowPart, adapter_id.HighPart))
+            .or_insert_with(|| get_adapter_device_path(adapter_id).ok().flatten());
+
+        map.by_path
+            .insert(target_name.monitorDevicePath, target_name);
+    }
+
+    Ok(map)
+}
+
+/// Queries `DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME` for a target.\
+/// A `None` value means access was denied, which occurs if the calling process does not have
+/// access to the current desktop or is running on a remote session.
+pub(crate) unsafe fn get_target_device_name(
+    adapter_id: LUID,
+    id: u32,
+) -> Result<Option<DISPLAYCONFIG_TARGET_DEVICE_NAME>, SysError> {
+    let mut target_name = DISPLAYCONFIG_TARGET_DEVICE_NAME::default();
+    target_name.header.size = size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32;
+    target_name.header.adapterId = adapter_id;
+    target_name.header.id = id;
+    target_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME;
+
+    match WIN32_ERROR(DisplayConfigGetDeviceInfo(&mut target_name.header) as u32) {
+        ERROR_SUCCESS => Ok(Some(target_name)),
+        ERROR_ACCESS_DENIED => Ok(None),
+        e => Err(SysError::DisplayConfigGetDeviceInfoFailed(e.into())),
+    }
+}
+
+/// Queries `DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME` for a source, returning its GDI device name
+/// (e.g. `\\.\DISPLAY1`).\
+/// A `None` value means access was denied, as with `get_target_device_name`.
+pub(crate) unsafe fn get_source_gdi_name(
+    adapter_id: LUID,
+    id: u32,
+) -> Result<Option<String>, SysError> {
+    let mut source_name = DISPLAYCONFIG_SOURCE_DEVICE_NAME::default();
+    source_name.header.size = size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32;
+    source_name.header.adapterId = adapter_id;
+    source_name.header.id = id;
+    source_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
+
+    match WIN32_ERROR(DisplayConfigGetDeviceInfo(&mut source_name.header) as u32) {
+        ERROR_SUCCESS => Ok(Some(wchar_to_string(&source_name.viewGdiDeviceName))),
+        ERROR_ACCESS_DENIED => Ok(None),
+        e => Err(SysError::DisplayConfigGetDeviceInfoFailed(e.into())),
+    }
+}
+
+/// Queries `DISPLAYCONFIG_DEVICE_INFO_GET_ADAPTER_NAME` for an adapter, returning its device
+/// instance path.\
+/// A `None` value means access was denied, as with `get_target_device_name`.
+pub(crate) unsafe fn get_adapter_device_path(adapter_id: LUID) -> Result<Option<String>, SysError> {
+    let mut adapter_name = DISPLAYCONFIG_ADAPTER_NAME::default();
+    adapter_name.header.size = size_of::<DISPLAYCONFIG_ADAPTER_NAME>() as u32;
+    adapter_name.header.adapterId = adapter_id;
+    adapter_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_ADAPTER_NAME;
+
+    match WIN32_ERROR(DisplayConfigGetDeviceInfo(&mut adapter_name.header) as u32) {
+        ERROR_SUCCESS => Ok(Some(wchar_to_string(&adapter_name.adapterDevicePath))),
+        ERROR_ACCESS_DENIED => Ok(None),
+        e => Err(SysError::DisplayConfigGetDeviceInfoFailed(e.into())),
+    }
+}
+
+/// Calls `GetDisplayConfigBufferSizes` and `QueryDisplayConfig` with the given flags, returning
+/// the `DISPLAYCONFIG` paths and modes truncated to the number actually written.
+pub(crate) unsafe fn query_display_config(
+    flags: QUERY_DISPLAY_CONFIG_FLAGS,
+) -> Result<(Vec<DISPLAYCONFIG_PATH_INFO>, Vec<DISPLAYCONFIG_MODE_INFO>), SysError> {
     let mut path_count = 0;
     let mut mode_count = 0;
-    GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count)
+    GetDisplayConfigBufferSizes(flags, &mut path_count, &mut mode_count)
         .ok()
         .map_err(SysError::GetDisplayConfigBufferSizesFailed)?;
     let mut display_paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
     let mut display_modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
     QueryDisplayConfig(
-        QDC_ONLY_ACTIVE_PATHS,
+        flags,
         &mut path_count,
         display_paths.as_mut_ptr(),
         &mut mode_count,
@@ -271,47 +2155,240 @@ unsafe fn get_device_info_map(
     )
     .ok()
     .map_err(SysError::QueryDisplayConfigFailed)?;
+    display_paths.truncate(path_count as usize);
+    display_modes.truncate(mode_count as usize);
+    Ok((display_paths, display_modes))
+}
 
-    display_modes
-        .into_iter()
-        .filter(|mode| mode.infoType == DISPLAYCONFIG_MODE_INFO_TYPE_TARGET)
-        .flat_map(|mode| {
-            let mut device_name = DISPLAYCONFIG_TARGET_DEVICE_NAME::default();
-            device_name.header.size = size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32;
-            device_name.header.adapterId = mode.adapterId;
-            device_name.header.id = mode.id;
-            device_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME;
-
-            match WIN32_ERROR(DisplayConfigGetDeviceInfo(&mut device_name.header) as u32) {
-                ERROR_SUCCESS => Some(Ok((device_name.monitorDevicePath, device_name))),
-                // This error occurs if the calling process does not have access to the current desktop or is running on a remote session.
-                ERROR_ACCESS_DENIED => None,
-                _ => Some(Err(SysError::DisplayConfigGetDeviceInfoFailed(
-                    WIN32_ERROR(DisplayConfigGetDeviceInfo(&mut device_name.header) as u32).into(),
-                ))),
-            }
-        })
-        .collect()
+/// Looks up the container ID of the monitor with the given Device Path (a device interface path),
+/// via its device instance.\
+/// A `None` value means the device interface, its device node or its container ID does not exist.
+pub(crate) unsafe fn container_id_for_device_path(
+    device_path: &str,
+) -> Result<Option<GUID>, SysError> {
+    if device_path.is_empty() {
+        return Ok(None);
+    }
+
+    let failed = |result: CONFIGRET| match result {
+        CR_NO_SUCH_DEVICE_INTERFACE | CR_NO_SUCH_DEVNODE | CR_NO_SUCH_VALUE => Ok(None),
+        result => Err(SysError::GettingContainerIdFailed {
+            device_path: device_path.to_owned(),
+            source: WIN32_ERROR(CM_MapCrToWin32Err(result, ERROR_GEN_FAILURE.0)).into(),
+        }),
+    };
+
+    let device_path = device_path
+        .encode_utf16()
+        .chain(once(0))
+        .collect::<Vec<_>>();
+
+    let mut property_type = DEVPROPTYPE::default();
+    let mut size = 0;
+    let result = CM_Get_Device_Interface_PropertyW(
+        PCWSTR(device_path.as_ptr()),
+        &DEVPKEY_Device_InstanceId,
+        &mut property_type,
+        None,
+        &mut size,
+        0,
+    );
+    if result != CR_BUFFER_SMALL {
+        return failed(result);
+    }
+
+    let mut instance_id = vec![0u16; size as usize / size_of::<u16>()];
+    let result = CM_Get_Device_Interface_PropertyW(
+        PCWSTR(device_path.as_ptr()),
+        &DEVPKEY_Device_InstanceId,
+        &mut property_type,
+        Some(instance_id.as_mut_ptr().cast()),
+        &mut size,
+        0,
+    );
+    if result != CR_SUCCESS {
+        return failed(result);
+    }
+
+    let mut dev_inst = 0;
+    let result = CM_Locate_DevNodeW(
+        &mut dev_inst,
+        PCWSTR(instance_id.as_ptr()),
+        CM_LOCATE_DEVNODE_NORMAL,
+    );
+    if result != CR_SUCCESS {
+        return failed(result);
+    }
+
+    let mut container_id = GUID::zeroed();
+    let mut size = size_of::<GUID>() as u32;
+    let result = CM_Get_DevNode_PropertyW(
+        dev_inst,
+        &DEVPKEY_Device_ContainerId,
+        &mut property_type,
+        Some((&mut container_id as *mut GUID).cast()),
+        &mut size,
+        0,
+    );
+    if result != CR_SUCCESS {
+        return failed(result);
+    }
+
+    Ok(Some(container_id))
+}
+
+/// Returns the Device Path of the target of every active path, in path priority order
+unsafe fn active_target_device_paths() -> Result<Vec<String>, SysError> {
+    let (paths, _) = query_display_config(QDC_ONLY_ACTIVE_PATHS)?;
+    let mut device_paths = Vec::with_capacity(paths.len());
+    for path in paths {
+        let target = path.targetInfo;
+        if let Some(name) = get_target_device_name(target.adapterId, target.id)? {
+            device_paths.push(wchar_to_string(&name.monitorDevicePath));
+        }
+    }
+    Ok(device_paths)
+}
+
+/// Finds the target of the active path whose target has the given Device Path.\
+/// A `None` value means the device has no active path.
+unsafe fn active_target_for_device_path(
+    device_path: &str,
+) -> Result<Option<DISPLAYCONFIG_PATH_TARGET_INFO>, SysError> {
+    let (paths, _) = query_display_config(QDC_ONLY_ACTIVE_PATHS)?;
+    for path in paths {
+        let target = path.targetInfo;
+        if get_target_device_name(target.adapterId, target.id)?
+            .is_some_and(|name| wchar_to_string(&name.monitorDevicePath) == device_path)
+        {
+            return Ok(Some(target));
+        }
+    }
+    Ok(None)
+}
+
+/// `advancedColorEnabled` in the bitfield of `DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO`
+const ADVANCED_COLOR_ENABLED: u32 = 0b10;
+
+/// The bit depth reported for monitors whose `bitsPerColorChannel` can't be queried
+const DEFAULT_BITS_PER_COLOR_CHANNEL: u32 = 8;
+
+/// Queries `DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO` for a target, which holds the color
+/// encoding of its signal and whether HDR is supported and enabled.
+unsafe fn get_advanced_color_info(
+    adapter_id: LUID,
+    id: u32,
+) -> Result<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO, SysError> {
+    let mut color_info = DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO::default();
+    color_info.header.size = size_of::<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO>() as u32;
+    color_info.header.adapterId = adapter_id;
+    color_info.header.id = id;
+    color_info.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO;
+
+    match WIN32_ERROR(DisplayConfigGetDeviceInfo(&mut color_info.header) as u32) {
+        ERROR_SUCCESS => Ok(color_info),
+        e => Err(SysError::DisplayConfigGetDeviceInfoFailed(e.into())),
+    }
+}
+
+/// Queries `DISPLAYCONFIG_DEVICE_INFO_GET_SDR_WHITE_LEVEL` for a target, returning the white level
+/// of SDR content in nits.\
+/// Note: This requires Windows 10 1709 or later.
+unsafe fn get_sdr_white_level_nits(adapter_id: LUID, id: u32) -> Result<f32, SysError> {
+    let mut white_level = DISPLAYCONFIG_SDR_WHITE_LEVEL::default();
+    white_level.header.size = size_of::<DISPLAYCONFIG_SDR_WHITE_LEVEL>() as u32;
+    white_level.header.adapterId = adapter_id;
+    white_level.header.id = id;
+    white_level.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SDR_WHITE_LEVEL;
+
+    match WIN32_ERROR(DisplayConfigGetDeviceInfo(&mut white_level.header) as u32) {
+        // The level is a multiplier of the 80 nit reference white, in thousandths
+        ERROR_SUCCESS => Ok(white_level.SDRWhiteLevel as f32 / 1000.0 * 80.0),
+        e => Err(SysError::DisplayConfigGetDeviceInfoFailed(e.into())),
+    }
+}
+
+/// Finds the `DISPLAYCONFIG_DESKTOP_IMAGE_INFO` of the active path whose target has the given
+/// Device Path, using the virtual mode aware path layout to locate its desktop mode.
+unsafe fn get_desktop_image_info(
+    device_path: &str,
+) -> Result<Option<DISPLAYCONFIG_DESKTOP_IMAGE_INFO>, SysError> {
+    let (paths, modes) = query_display_config(QDC_ONLY_ACTIVE_PATHS | QDC_VIRTUAL_MODE_AWARE)?;
+    for path in paths {
+        let target = path.targetInfo;
+        let Some(name) = get_target_device_name(target.adapterId, target.id)? else {
+            continue;
+        };
+        if wchar_to_string(&name.monitorDevicePath) != device_path {
+            continue;
+        }
+
+        // The low 16 bits of the bitfield are desktopModeInfoIdx, 0xFFFF when invalid
+        let idx = (target.Anonymous.Anonymous._bitfield & 0xFFFF) as usize;
+        return Ok(modes
+            .get(idx)
+            .filter(|mode| mode.infoType == DISPLAYCONFIG_MODE_INFO_TYPE_DESKTOP_IMAGE)
+            .map(|mode| mode.Anonymous.desktopImageInfo));
+    }
+
+    Ok(None)
+}
+
+/// Finds the video signal of the target mode of the active path whose target has the given Device
+/// Path.\
+/// A `None` value means the device has no active path (or the path has no target mode).
+unsafe fn get_target_signal_info(
+    device_path: &str,
+) -> Result<Option<DISPLAYCONFIG_VIDEO_SIGNAL_INFO>, SysError> {
+    let (paths, modes) = query_display_config(QDC_ONLY_ACTIVE_PATHS)?;
+    for path in paths {
+        let target = path.targetInfo;
+        let Some(name) = get_target_device_name(target.adapterId, target.id)? else {
+            continue;
+        };
+        if wchar_to_string(&name.monitorDevicePath) != device_path {
+            continue;
+        }
+
+        // Without QDC_VIRTUAL_MODE_AWARE the union holds modeInfoIdx, which is
+        // DISPLAYCONFIG_PATH_MODE_IDX_INVALID when invalid
+        let idx = target.Anonymous.modeInfoIdx as usize;
+        return Ok(modes
+            .get(idx)
+            .filter(|mode| mode.infoType == DISPLAYCONFIG_MODE_INFO_TYPE_TARGET)
+            .map(|mode| mode.Anonymous.targetMode.targetVideoSignalInfo));
+    }
+
+    Ok(None)
 }
 
 /// Calls `EnumDisplayMonitors` and returns a list of `HMONITOR` handles.\
 /// Note that a `HMONITOR` is a logical construct that may correspond to multiple physical monitors.\
 /// e.g. when in "Duplicate" mode two physical monitors will belong to the same `HMONITOR`
-unsafe fn enum_display_monitors() -> Result<Vec<HMONITOR>, SysError> {
+pub(crate) unsafe fn enum_display_monitors() -> Result<Vec<HMONITOR>, SysError> {
+    Ok(enum_display_monitor_rects()?
+        .into_iter()
+        .map(|(hmonitor, _)| hmonitor)
+        .collect())
+}
+
+/// Like `enum_display_monitors`, but also returns the rectangle of each `HMONITOR` in virtual
+/// desktop coordinates, as passed to the `EnumDisplayMonitors` callback.
+unsafe fn enum_display_monitor_rects() -> Result<Vec<(HMONITOR, RECT)>, SysError> {
     unsafe extern "system" fn enum_monitors(
         handle: HMONITOR,
         _: HDC,
-        _: *mut RECT,
+        rect: *mut RECT,
         data: LPARAM,
     ) -> BOOL {
-        let monitors = &mut *(data.0 as *mut Vec<HMONITOR>);
-        monitors.push(handle);
+        let monitors = &mut *(data.0 as *mut Vec<(HMONITOR, RECT)>);
+        monitors.push((handle, *rect));
         true.into()
     }
-    let mut hmonitors = Vec::<HMONITOR>::new();
+    let mut hmonitors = Vec::<(HMONITOR, RECT)>::new();
     EnumDisplayMonitors(
         HDC::default(),
-        Some(ptr::null_mut()),
+        None,
         Some(enum_monitors),
         LPARAM(&mut hmonitors as *mut _ as isize),
     )
@@ -327,14 +2404,18 @@ unsafe fn enum_display_monitors() -> Result<Vec<HMONITOR>, SysError> {
 unsafe fn get_physical_monitors_from_hmonitor(
     hmonitor: HMONITOR,
 ) -> Result<Vec<WrappedPhysicalMonitor>, SysError> {
+    let error = |source| SysError::GetPhysicalMonitorsFailed {
+        hmonitor: hmonitor.0 as isize,
+        device_name: monitor_device_name(hmonitor),
+        source,
+    };
+
     let mut physical_number: u32 = 0;
-    GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut physical_number)
-        .map_err(SysError::GetPhysicalMonitorsFailed)?;
+    GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut physical_number).map_err(error)?;
     let mut raw_physical_monitors = vec![PHYSICAL_MONITOR::default(); physical_number as usize];
     // Allocate first so that pushing the wrapped handles always succeeds.
     let mut physical_monitors = Vec::with_capacity(raw_physical_monitors.len());
-    GetPhysicalMonitorsFromHMONITOR(hmonitor, &mut raw_physical_monitors)
-        .map_err(SysError::GetPhysicalMonitorsFailed)?;
+    GetPhysicalMonitorsFromHMONITOR(hmonitor, &mut raw_physical_monitors).map_err(error)?;
     // Transform immediately into WrappedPhysicalMonitor so the handles don't leak
     raw_physical_monitors
         .into_iter()
@@ -342,19 +2423,132 @@ unsafe fn get_physical_monitors_from_hmonitor(
     Ok(physical_monitors)
 }
 
-/// Gets the list of display devices that belong to a `HMONITOR`.\
-/// Due to the `EDD_GET_DEVICE_INTERFACE_NAME` flag, the `DISPLAY_DEVICEW` will contain the DOS
-/// device path for each monitor in the `DeviceID` field.\
-/// Note: Connected but inactive displays have been filtered out.
-unsafe fn get_display_devices_from_hmonitor(
+/// Acquires the physical monitor handle for a device path, matched to its display device by index
+/// in the same way as `connected_displays_physical`.\
+/// A `None` value means the device is no longer attached to the `HMONITOR`.
+unsafe fn physical_monitor_for_path(
     hmonitor: HMONITOR,
-) -> Result<Vec<(MONITORINFOEXW, DISPLAY_DEVICEW)>, SysError> {
+    device_path: &str,
+) -> Result<Option<(WrappedPhysicalMonitor, DISPLAY_DEVICEW)>, SysError> {
+    let physical_monitors = match get_physical_monitors_from_hmonitor(hmonitor) {
+        Ok(p) => p,
+        // See connected_displays_physical_with
+        Err(SysError::GetPhysicalMonitorsFailed { source, .. })
+            if source.code() == ERROR_GEN_FAILURE.to_hresult() =>
+        {
+            return Ok(None)
+        }
+        Err(e) => return Err(e),
+    };
+    let display_devices = get_active_display_devices_from_hmonitor(hmonitor)?;
+    let device_info_map = get_device_info_map()?;
+    if display_devices.len() != physical_monitors.len()
+        || !display_devices
+            .iter()
+            .all(|(_, display_device)| device_info_map.source_matches(display_device))
+    {
+        return Err(enumeration_mismatch(
+            hmonitor,
+            physical_monitors.len(),
+            display_devices.len(),
+        ));
+    }
+
+    Ok(physical_monitors
+        .into_iter()
+        .zip(display_devices)
+        .find(|(_, (_, display_device))| wchar_to_string(&display_device.DeviceID) == device_path)
+        .map(|(physical_monitor, (_, display_device))| (physical_monitor, display_device)))
+}
+
+/// Calls `GetMonitorInfoW` for a `HMONITOR`, returning the extended info which includes the GDI
+/// device name in `szDevice`.
+pub(crate) unsafe fn get_monitor_info(hmonitor: HMONITOR) -> Result<MONITORINFOEXW, SysError> {
     let mut info = MONITORINFOEXW::default();
     info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
     let info_ptr = &mut info as *mut _ as *mut MONITORINFO;
     GetMonitorInfoW(hmonitor, info_ptr)
         .ok()
-        .map_err(SysError::GetMonitorInfoFailed)?;
+        .map_err(|source| SysError::GetMonitorInfoFailed {
+            hmonitor: hmonitor.0 as isize,
+            source,
+        })?;
+    Ok(info)
+}
+
+/// Returns the GDI device name of a `HMONITOR` for error messages, or `"unknown device"` when it
+/// can't be read (e.g. because the `HMONITOR` is stale).
+unsafe fn monitor_device_name(hmonitor: HMONITOR) -> String {
+    get_monitor_info(hmonitor).map_or_else(
+        |_| "unknown device".to_string(),
+        |info| wchar_to_string(&info.szDevice),
+    )
+}
+
+unsafe fn enumeration_mismatch(
+    hmonitor: HMONITOR,
+    physical_monitors: usize,
+    display_devices: usize,
+) -> SysError {
+    SysError::EnumerationMismatch {
+        hmonitor: hmonitor.0 as isize,
+        device_name: monitor_device_name(hmonitor),
+        physical_monitors,
+        display_devices,
+    }
+}
+
+/// Calls `GetDpiForMonitor` with `MDT_EFFECTIVE_DPI`, returning the `(x, y)` DPI of a `HMONITOR`
+unsafe fn get_dpi_for_monitor(hmonitor: HMONITOR) -> Result<(u32, u32), SysError> {
+    let mut dpi_x = 0;
+    let mut dpi_y = 0;
+    GetDpiForMonitor(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y).map_err(|source| {
+        SysError::GetDpiFailed {
+            hmonitor: hmonitor.0 as isize,
+            device_name: monitor_device_name(hmonitor),
+            source,
+        }
+    })?;
+    Ok((dpi_x, dpi_y))
+}
+
+/// Returns the effective DPI of a monitor and its scale factor, where 96 DPI is `1.0`.\
+/// `GetDpiForMonitor` only fails for invalid handles, in which case the monitor is treated as
+/// unscaled rather than failing the enumeration.
+unsafe fn get_scale_for_monitor(hmonitor: HMONITOR) -> (u32, f64) {
+    let dpi = get_dpi_for_monitor(hmonitor).map_or(USER_DEFAULT_SCREEN_DPI, |(dpi_x, _)| dpi_x);
+    (dpi, f64::from(dpi) / f64::from(USER_DEFAULT_SCREEN_DPI))
+}
+
+/// Calls `EnumDisplayDevicesW` until it runs out of devices, including inactive ones
+pub(crate) unsafe fn enum_display_devices(device: PCWSTR) -> Vec<DISPLAY_DEVICEW> {
+    (0..)
+        .map_while(|device_number| {
+            let mut display_device = DISPLAY_DEVICEW {
+                cb: size_of::<DISPLAY_DEVICEW>() as u32,
+                ..Default::default()
+            };
+            EnumDisplayDevicesW(
+                device,
+                device_number,
+                &mut display_device,
+                EDD_GET_DEVICE_INTERFACE_NAME,
+            )
+            .as_bool()
+            .then_some(display_device)
+        })
+        .collect()
+}
+
+/// Gets the list of display devices that belong to a `HMONITOR`.\
+/// Due to the `EDD_GET_DEVICE_INTERFACE_NAME` flag, the `DISPLAY_DEVICEW` will contain the DOS
+/// device path for each monitor in the `DeviceID` field.\
+/// Note: Inactive displays are included, check `StateFlags` for `DISPLAY_DEVICE_ACTIVE` and
+/// `DISPLAY_DEVICE_ATTACHED`.
+unsafe fn get_display_devices_from_hmonitor(
+    hmonitor: HMONITOR,
+) -> Result<Vec<(MONITORINFOEXW, DISPLAY_DEVICEW)>, SysError> {
+    let info = get_monitor_info(hmonitor)?;
     Ok((0..)
         .map_while(|device_number| {
             let mut device = DISPLAY_DEVICEW {
@@ -370,56 +2564,117 @@ unsafe fn get_display_devices_from_hmonitor(
             .as_bool()
             .then_some(device)
         })
-        .filter(|device| flag_set(device.StateFlags, DISPLAY_DEVICE_ACTIVE))
         .map(|device| (info, device))
         .collect())
 }
 
+/// The active display devices of a `HMONITOR`, which are the ones with a physical monitor handle,
+/// in the same order as `get_physical_monitors_from_hmonitor`.
+unsafe fn get_active_display_devices_from_hmonitor(
+    hmonitor: HMONITOR,
+) -> Result<Vec<(MONITORINFOEXW, DISPLAY_DEVICEW)>, SysError> {
+    let mut display_devices = get_display_devices_from_hmonitor(hmonitor)?;
+    display_devices
+        .retain(|(_, display_device)| flag_set(display_device.StateFlags, DISPLAY_DEVICE_ACTIVE));
+    Ok(display_devices)
+}
+
 /// A lenient version of get_display_devices_from_hmonitor which provides
-/// more fault-tolerance for virtualized monitors
+/// more fault-tolerance for virtualized monitors.\
+/// The adapter at the index of the `HMONITOR` is only a guess, so it is verified against the GDI
+/// name of the `HMONITOR` and replaced by the adapter with that name when they disagree. The
+/// Device Path of the `DISPLAYCONFIG` target paired with that source (by
+/// `DISPLAYCONFIG_SOURCE_DEVICE_NAME`) is preferred over the adapter's first monitor.
 unsafe fn get_display_devices_from_hmonitor_lenient(
     idx: usize,
     hmonitor: HMONITOR,
+    device_info_map: &DeviceInfoMap,
 ) -> Result<Vec<(MONITORINFOEXW, DISPLAY_DEVICEW)>, SysError> {
-    let mut info = MONITORINFOEXW::default();
-    info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
-    let info_ptr = &mut info as *mut _ as *mut MONITORINFO;
+    let info = get_monitor_info(hmonitor)?;
+    let gdi_name = wchar_to_string(&info.szDevice);
 
-    GetMonitorInfoW(hmonitor, info_ptr)
-        .ok()
-        .map_err(SysError::GetMonitorInfoFailed)?;
+    let adapters = enum_display_devices(PCWSTR::null());
+    let mut device = adapters
+        .iter()
+        .find(|adapter| wchar_to_string(&adapter.DeviceName) == gdi_name)
+        .or_else(|| adapters.get(idx))
+        .copied()
+        .unwrap_or(DISPLAY_DEVICEW {
+            cb: size_of::<DISPLAY_DEVICEW>() as u32,
+            ..Default::default()
+        });
 
-    let mut device = DISPLAY_DEVICEW {
-        cb: size_of::<DISPLAY_DEVICEW>() as u32,
-        ..Default::default()
-    };
+    match device_info_map
+        .by_gdi_name
+        .get(gdi_name.as_str())
+        .map(Vec::as_slice)
+    {
+        Some([target_name]) => device.DeviceID = target_name.monitorDevicePath,
+        _ => {
+            if let Some(device_with_id) = enum_display_devices(PCWSTR(device.DeviceName.as_ptr()))
+                .into_iter()
+                .next()
+            {
+                device.DeviceID = device_with_id.DeviceID;
+            }
+        }
+    }
+
+    Ok(vec![(info, device)])
+}
 
-    let mut device_with_id = DISPLAY_DEVICEW {
-        cb: size_of::<DISPLAY_DEVICEW>() as u32,
+/// Calls `EnumDisplaySettingsW` with `ENUM_CURRENT_SETTINGS` for a GDI device name (e.g.
+/// `MONITORINFOEXW.szDevice`) and returns the current display mode.\
+/// The device builders fall back to `DEVMODEW::default()` on failure, i.e. a landscape mode with
+/// a zero resolution and refresh rate, rather than failing the whole device.
+unsafe fn get_current_display_settings(gdi_device_name: &[u16]) -> Result<DEVMODEW, SysError> {
+    let mut mode = DEVMODEW {
+        dmSize: size_of::<DEVMODEW>() as u16,
         ..Default::default()
     };
-
-    if EnumDisplayDevicesW(
-        PCWSTR::null(),
-        idx as u32,
-        &mut device,
-        EDD_GET_DEVICE_INTERFACE_NAME,
+    if EnumDisplaySettingsW(
+        PCWSTR(gdi_device_name.as_ptr()),
+        ENUM_CURRENT_SETTINGS,
+        &mut mode,
     )
     .as_bool()
     {
-        let _ = EnumDisplayDevicesW(
-            PCWSTR(device.DeviceName.as_ptr()),
-            0,
-            &mut device_with_id,
-            EDD_GET_DEVICE_INTERFACE_NAME,
-        );
+        Ok(mode)
+    } else {
+        Err(SysError::EnumDisplaySettingsFailed {
+            device_name: wchar_to_string(gdi_device_name),
+        })
     }
+}
 
-    device.DeviceID = device_with_id.DeviceID;
-
-    Ok(vec![(info, device)])
+/// Calls `EnumDisplaySettingsExW` for a GDI device name until it runs out of modes, returning
+/// every display mode the driver reports.
+unsafe fn get_display_modes(gdi_device_name: &[u16]) -> Vec<DEVMODEW> {
+    (0..)
+        .map_while(|mode_number| {
+            let mut mode = DEVMODEW {
+                dmSize: size_of::<DEVMODEW>() as u16,
+                ..Default::default()
+            };
+            EnumDisplaySettingsExW(
+                PCWSTR(gdi_device_name.as_ptr()),
+                ENUM_DISPLAY_SETTINGS_MODE(mode_number),
+                &mut mode,
+                ENUM_DISPLAY_SETTINGS_FLAGS(0),
+            )
+            .as_bool()
+            .then_some(mode)
+        })
+        .collect()
 }
 
+/// The `CreateFileW` errors of display devices which have no real device interface
+const SKIPPED_DEVICE_INTERFACE_ERRORS: [WIN32_ERROR; 3] = [
+    ERROR_ACCESS_DENIED,
+    ERROR_FILE_NOT_FOUND,
+    ERROR_PATH_NOT_FOUND,
+];
+
 /// Opens and returns a file handle for a display device using its DOS device path.\
 /// These handles are only used for the `DeviceIoControl` API (for internal displays); a
 /// handle can still be returned for external displays, but it should not be used.\
@@ -439,9 +2694,11 @@ unsafe fn get_file_handle_for_display_device(
     )
     .map(|h| Some(WrappedFileHandle(h)))
     .or_else(|e| {
-        // This error occurs for virtual devices e.g. Remote Desktop
-        // sessions - they are not real monitors
-        (e.code() == ERROR_ACCESS_DENIED.to_hresult())
+        // These errors occur for virtual devices e.g. Remote Desktop sessions (access denied) and
+        // virtual display drivers such as spacedesk (not found), which are not real monitors
+        SKIPPED_DEVICE_INTERFACE_ERRORS
+            .iter()
+            .any(|skipped| e.code() == skipped.to_hresult())
             .then_some(None)
             .ok_or_else(|| SysError::OpeningMonitorDeviceInterfaceHandleFailed {
                 device_name: wchar_to_string(&display_device.DeviceName),
@@ -450,8 +2707,198 @@ unsafe fn get_file_handle_for_display_device(
     })
 }
 
-fn wchar_to_string(s: &[u16]) -> String {
+/// The 64-bit FNV-1a hash, which unlike `DefaultHasher` is guaranteed to be the same across Rust
+/// versions, so that it can be persisted
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Returns the GDI device name (e.g. `\\.\DISPLAY1`) of a display device name, which for
+/// monitors has an extra path segment (e.g. `\\.\DISPLAY1\Monitor0`).
+pub(crate) fn gdi_device_name(device_name: &str) -> &str {
+    const PREFIX: &str = r"\\.\";
+    device_name
+        .get(PREFIX.len()..)
+        .and_then(|rest| rest.find('\\'))
+        .map_or(device_name, |end| &device_name[..PREFIX.len() + end])
+}
+
+pub(crate) fn wchar_to_string(s: &[u16]) -> String {
     let end = s.iter().position(|&x| x == 0).unwrap_or(s.len());
     let truncated = &s[0..end];
     OsString::from_wide(truncated).to_string_lossy().into()
 }
+
+/// Returns the `monitorFriendlyDeviceName` of a target, or the description of the display device
+/// when the target is unknown or has no friendly name (e.g. some generic monitors).
+fn friendly_name(
+    target_name: Option<&DISPLAYCONFIG_TARGET_DEVICE_NAME>,
+    display_device: &DISPLAY_DEVICEW,
+) -> String {
+    target_name
+        .map(|d| description_to_string(&d.monitorFriendlyDeviceName))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| description_to_string(&display_device.DeviceString))
+}
+
+/// Like `wchar_to_string`, but trims and collapses the whitespace some drivers pad descriptions
+/// with, so that they display cleanly and compare equal.\
+/// Only used for human-readable fields: paths and keys must be kept verbatim.
+pub(crate) fn description_to_string(s: &[u16]) -> String {
+    wchar_to_string(s)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn description_to_string_trims_and_collapses_padding() {
+        let mut description = [0u16; 128];
+        for (dst, src) in description
+            .iter_mut()
+            .zip("  Generic   PnP\tMonitor   ".encode_utf16())
+        {
+            *dst = src;
+        }
+
+        assert_eq!(description_to_string(&description), "Generic PnP Monitor");
+    }
+
+    #[test]
+    fn devices_without_a_device_path_are_compared_by_handle_and_name() {
+        let device = |hmonitor, device_name, device_path| {
+            Device::new_for_test(
+                MonitorHandle::new(hmonitor),
+                RECT::default(),
+                RECT::default(),
+                device_name,
+                device_path,
+            )
+        };
+
+        assert_eq!(
+            device(1, r"\\.\DISPLAY1", "a"),
+            device(2, r"\\.\DISPLAY2", "a")
+        );
+        assert_ne!(
+            device(1, r"\\.\DISPLAY1", ""),
+            device(2, r"\\.\DISPLAY1", "")
+        );
+        assert_ne!(
+            device(1, r"\\.\DISPLAY1", ""),
+            device(1, r"\\.\DISPLAY2", "")
+        );
+        assert_eq!(
+            device(1, r"\\.\DISPLAY1", ""),
+            device(1, r"\\.\DISPLAY1", "")
+        );
+    }
+
+    #[test]
+    fn fnv1a_64_matches_reference_vectors() {
+        assert_eq!(fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a_64(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn stable_id_with_a_serial_ignores_the_device_path() {
+        let device = |device_path, serial_string: Option<&str>| {
+            let mut device = Device::new_for_test(
+                MonitorHandle::new(1),
+                RECT::default(),
+                RECT::default(),
+                r"\\.\DISPLAY1",
+                device_path,
+            );
+            device.edid = Some(EdidInfo {
+                manufacturer_id: String::from("DEL"),
+                product_code: 0xA0C2,
+                serial_number: Some(0x4C4C_3031),
+                serial_string: serial_string.map(String::from),
+                manufacture_week: None,
+                manufacture_year: None,
+            });
+            device
+        };
+        let first_port =
+            r"\\?\DISPLAY#DELA0C2#5&1a2b3c4d&0&UID4352#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}";
+        let second_port =
+            r"\\?\DISPLAY#DELA0C2#5&1a2b3c4d&0&UID4353#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}";
+
+        assert_eq!(
+            device(first_port, Some("7MT0187Q0N1L")).stable_id(),
+            "DEL-A0C2-7MT0187Q0N1L"
+        );
+        assert_eq!(
+            device(first_port, Some("7MT0187Q0N1L")).stable_id(),
+            device(second_port, Some("7MT0187Q0N1L")).stable_id()
+        );
+        assert_eq!(device(first_port, None).stable_id(), "DEL-A0C2-4C4C3031");
+        assert_eq!(
+            device(first_port, None).stable_id(),
+            device(second_port, None).stable_id()
+        );
+    }
+
+    #[test]
+    fn layout_ranks_count_the_longest_chain_before_each_display() {
+        let displays = |spans: &[(i32, i32)]| {
+            spans
+                .iter()
+                .enumerate()
+                .map(|(idx, &(left, right))| {
+                    let size = RECT {
+                        left,
+                        top: 0,
+                        right,
+                        bottom: 1080,
+                    };
+                    Device::new_for_test(
+                        MonitorHandle::new(idx as isize + 1),
+                        size,
+                        size,
+                        format!(r"\\.\DISPLAY{}", idx + 1),
+                        "",
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let columns = |displays: &[Device]| layout_ranks(displays, |rect| (rect.left, rect.right));
+
+        // Touching edges count as lying before, regardless of the order of the displays
+        assert_eq!(
+            columns(&displays(&[(3840, 5760), (0, 1920), (1920, 3840)])),
+            vec![2, 0, 1]
+        );
+        // Displays starting at the same coordinate tie, and the one after them ranks past both
+        assert_eq!(
+            columns(&displays(&[(0, 1920), (0, 2560), (2560, 4480)])),
+            vec![0, 0, 1]
+        );
+        // Overlapping spans share a rank, even when only part of one overlaps the other
+        assert_eq!(
+            columns(&displays(&[(0, 1920), (1000, 2920), (2920, 4840)])),
+            vec![0, 0, 1]
+        );
+        // The rank follows the longest chain, not the number of displays before
+        assert_eq!(
+            columns(&displays(&[
+                (0, 3840),
+                (0, 1920),
+                (1920, 3840),
+                (3840, 5760)
+            ])),
+            vec![0, 0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use windows::core::PCWSTR;
+use windows::Win32::Devices::Display::QDC_ALL_PATHS;
+use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_PATH_ACTIVE;
+
+use crate::device;
+use crate::device::enum_display_devices;
+use crate::device::wchar_to_string;
+use crate::output_technology::output_technology_name;
+use crate::query::DisplayQuery;
+
+/// Produces a human-readable, multi-line report of everything this crate can see about the
+/// connected displays, intended to be attached to bug reports.\
+/// This includes inactive display devices, every `DISPLAYCONFIG` path (not just active ones), the
+/// results of the regular enumeration functions, and any errors encountered along the way.
+pub fn diagnostic_report() -> String {
+    let mut report = String::new();
+
+    let _ = writeln!(
+        report,
+        "win32-display-data {} diagnostic report",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let _ = writeln!(report, "\n== Display devices (EnumDisplayDevicesW) ==");
+    unsafe { write_display_devices(&mut report) };
+
+    let _ = writeln!(report, "\n== Monitors (EnumDisplayMonitors) ==");
+    unsafe { write_monitors(&mut report) };
+
+    let _ = writeln!(report, "\n== DisplayConfig paths (QDC_ALL_PATHS) ==");
+    unsafe { write_display_config_paths(&mut report) };
+
+    let _ = writeln!(report, "\n== Devices (connected_displays_all) ==");
+    for device in device::connected_displays_all() {
+        match device {
+            Ok(device) => {
+                let _ = writeln!(report, "{device:#?}");
+            }
+            Err(e) => write_error(&mut report, "", &e),
+        }
+    }
+
+    let _ = writeln!(report, "\n== Physical devices (lenient) ==");
+    match DisplayQuery::new().lenient(true).collect_physical() {
+        Ok(devices) => {
+            for device in devices {
+                let _ = writeln!(report, "{device:#?}");
+            }
+        }
+        Err(e) => write_error(&mut report, "", &e),
+    }
+
+    report
+}
+
+unsafe fn write_display_devices(report: &mut String) {
+    for adapter in enum_display_devices(PCWSTR::null()) {
+        let _ = writeln!(
+            report,
+            "{} {:?} flags={:#010x}",
+            wchar_to_string(&adapter.DeviceName),
+            wchar_to_string(&adapter.DeviceString),
+            adapter.StateFlags,
+        );
+        let _ = writeln!(report, "  id: {}", wchar_to_string(&adapter.DeviceID));
+        let _ = writeln!(report, "  key: {}", wchar_to_string(&adapter.DeviceKey));
+
+        for monitor in enum_display_devices(PCWSTR(adapter.DeviceName.as_ptr())) {
+            let _ = writeln!(
+                report,
+                "  {} {:?} flags={:#010x}",
+                wchar_to_string(&monitor.DeviceName),
+                wchar_to_string(&monitor.DeviceString),
+                monitor.StateFlags,
+            );
+            let _ = writeln!(report, "    path: {}", wchar_to_string(&monitor.DeviceID));
+            let _ = writeln!(report, "    key: {}", wchar_to_string(&monitor.DeviceKey));
+        }
+    }
+}
+
+unsafe fn write_monitors(report: &mut String) {
+    let hmonitors = match device::enum_display_monitors() {
+        Ok(hmonitors) => hmonitors,
+        Err(e) => return write_error(report, "", &e),
+    };
+
+    for hmonitor in hmonitors {
+        let _ = writeln!(report, "HMONITOR {:#x}", hmonitor.0 as isize);
+        match device::get_monitor_info(hmonitor) {
+            Ok(info) => {
+                let rect = info.monitorInfo.rcMonitor;
+                let work = info.monitorInfo.rcWork;
+                let _ = writeln!(report, "  device: {}", wchar_to_string(&info.szDevice));
+                let _ = writeln!(
+                    report,
+                    "  rect: ({}, {}, {}, {}) work: ({}, {}, {}, {}) flags={:#x}",
+                    rect.left,
+                    rect.top,
+                    rect.right,
+                    rect.bottom,
+                    work.left,
+                    work.top,
+                    work.right,
+                    work.bottom,
+                    info.monitorInfo.dwFlags,
+                );
+            }
+            Err(e) => write_error(report, "  ", &e),
+        }
+    }
+}
+
+unsafe fn write_display_config_paths(report: &mut String) {
+    let paths = match device::query_display_config(QDC_ALL_PATHS) {
+        Ok((paths, _)) => paths,
+        Err(e) => return write_error(report, "", &e),
+    };
+
+    // With QDC_ALL_PATHS every possible source/target combination is returned, so only report
+    // the targets which are actually available to keep the output readable
+    for (idx, path) in paths.iter().enumerate().filter(|(_, path)| {
+        path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0 || path.targetInfo.targetAvailable.as_bool()
+    }) {
+        let source = path.sourceInfo;
+        let target = path.targetInfo;
+        let _ = writeln!(
+            report,
+            "Path {idx}: active={} flags={:#x}",
+            path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0,
+            path.flags,
+        );
+        let _ = writeln!(
+            report,
+            "  source: adapter=({:#x}, {:#x}) id={} status={:#x}",
+            source.adapterId.LowPart, source.adapterId.HighPart, source.id, source.statusFlags,
+        );
+        match device::get_source_gdi_name(source.adapterId, source.id) {
+            Ok(name) => {
+                let _ = writeln!(report, "    gdi name: {name:?}");
+            }
+            Err(e) => write_error(report, "    ", &e),
+        }
+        let _ = writeln!(
+            report,
+            "  target: adapter=({:#x}, {:#x}) id={} available={} status={:#x} output={}",
+            target.adapterId.LowPart,
+            target.adapterId.HighPart,
+            target.id,
+            target.targetAvailable.as_bool(),
+            target.statusFlags,
+            output_technology_name(target.outputTechnology),
+        );
+        match device::get_target_device_name(target.adapterId, target.id) {
+            Ok(Some(name)) => {
+                let _ = writeln!(
+                    report,
+                    "    friendly name: {:?}",
+                    wchar_to_string(&name.monitorFriendlyDeviceName)
+                );
+                let _ = writeln!(
+                    report,
+                    "    path: {}",
+                    wchar_to_string(&name.monitorDevicePath)
+                );
+            }
+            Ok(None) => {
+                let _ = writeln!(report, "    target name: access denied");
+            }
+            Err(e) => write_error(report, "    ", &e),
+        }
+    }
+}
+
+/// Writes an error and its full source chain
+fn write_error(report: &mut String, indent: &str, error: &dyn std::error::Error) {
+    let _ = write!(report, "{indent}error: {error}");
+    let mut source = error.source();
+    while let Some(e) = source {
+        let _ = write!(report, ": {e}");
+        source = e.source();
+    }
+    let _ = writeln!(report);
+}
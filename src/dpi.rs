@@ -0,0 +1,72 @@
+use windows::Win32::UI::HiDpi::AreDpiAwarenessContextsEqual;
+use windows::Win32::UI::HiDpi::GetThreadDpiAwarenessContext;
+use windows::Win32::UI::HiDpi::SetThreadDpiAwarenessContext;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_SYSTEM_AWARE;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_UNAWARE;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_UNAWARE_GDISCALED;
+
+use crate::device;
+
+/// A DPI awareness level, mirroring the `DPI_AWARENESS_CONTEXT_*` values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DpiAwareness {
+    Unaware,
+    UnawareGdiScaled,
+    SystemAware,
+    PerMonitorAware,
+    PerMonitorAwareV2,
+}
+
+const CONTEXTS: [(DPI_AWARENESS_CONTEXT, DpiAwareness); 5] = [
+    (DPI_AWARENESS_CONTEXT_UNAWARE, DpiAwareness::Unaware),
+    (
+        DPI_AWARENESS_CONTEXT_UNAWARE_GDISCALED,
+        DpiAwareness::UnawareGdiScaled,
+    ),
+    (
+        DPI_AWARENESS_CONTEXT_SYSTEM_AWARE,
+        DpiAwareness::SystemAware,
+    ),
+    (
+        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE,
+        DpiAwareness::PerMonitorAware,
+    ),
+    (
+        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+        DpiAwareness::PerMonitorAwareV2,
+    ),
+];
+
+/// Reads the DPI awareness of the calling thread (which is the process default unless the thread
+/// has overridden it) with `GetThreadDpiAwarenessContext`.
+pub fn current_process_dpi_awareness() -> DpiAwareness {
+    unsafe {
+        let context = GetThreadDpiAwarenessContext();
+        CONTEXTS
+            .iter()
+            .find(|(c, _)| AreDpiAwarenessContextsEqual(context, *c).as_bool())
+            .map_or(DpiAwareness::Unaware, |(_, awareness)| *awareness)
+    }
+}
+
+/// Returns `PerMonitorAwareV2` when the monitors have differing DPIs, and `SystemAware` when they
+/// all share the same DPI.\
+/// If the monitors can't be enumerated, `PerMonitorAwareV2` is returned as the safe choice.
+pub fn recommended_dpi_awareness() -> DpiAwareness {
+    unsafe {
+        // Without per-monitor awareness GetDpiForMonitor always reports 96 DPI
+        let previous = SetThreadDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+        let dpis = device::connected_displays_all()
+            .map(|device| device.and_then(|device| device.dpi()))
+            .collect::<Result<Vec<_>, _>>();
+        SetThreadDpiAwarenessContext(previous);
+
+        match dpis {
+            Ok(dpis) if dpis.windows(2).all(|pair| pair[0] == pair[1]) => DpiAwareness::SystemAware,
+            _ => DpiAwareness::PerMonitorAwareV2,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use windows::core::Interface;
+use windows::Win32::Graphics::Dxgi::Common::DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020;
+use windows::Win32::Graphics::Dxgi::CreateDXGIFactory1;
+use windows::Win32::Graphics::Dxgi::IDXGIFactory1;
+use windows::Win32::Graphics::Dxgi::IDXGIOutput6;
+use windows::Win32::Graphics::Dxgi::DXGI_ERROR_NOT_FOUND;
+use windows::Win32::Graphics::Dxgi::DXGI_OUTPUT_DESC1;
+
+use crate::device::wchar_to_string;
+use crate::error::SysError;
+
+/// The luminance range of an HDR display as reported by DXGI, in nits (cd/m²)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LuminanceRange {
+    pub min_luminance: f32,
+    pub max_luminance: f32,
+    pub max_full_frame_luminance: f32,
+}
+
+impl LuminanceRange {
+    /// Returns the luminance range from a DXGI output description, or `None` if the output is not
+    /// currently using an HDR (ST.2084 / BT.2020) color space.
+    pub(crate) fn from_output_desc(desc: &DXGI_OUTPUT_DESC1) -> Option<Self> {
+        (desc.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020).then_some(Self {
+            min_luminance: desc.MinLuminance,
+            max_luminance: desc.MaxLuminance,
+            max_full_frame_luminance: desc.MaxFullFrameLuminance,
+        })
+    }
+}
+
+/// Walks every output of every DXGI adapter and returns the `DXGI_OUTPUT_DESC1` of the output
+/// whose `DeviceName` matches the given GDI device name (e.g. `\\.\DISPLAY1`).\
+/// A `None` value means no DXGI output drives this GDI device (e.g. Remote Desktop sessions).
+pub(crate) unsafe fn output_desc_for_gdi_name(
+    gdi_name: &str,
+) -> Result<Option<DXGI_OUTPUT_DESC1>, SysError> {
+    let factory = CreateDXGIFactory1::<IDXGIFactory1>().map_err(SysError::DxgiQueryFailed)?;
+
+    for adapter_idx in 0.. {
+        let adapter = match factory.EnumAdapters1(adapter_idx) {
+            Ok(adapter) => adapter,
+            Err(e) if e.code() == DXGI_ERROR_NOT_FOUND => break,
+            Err(e) => return Err(SysError::DxgiQueryFailed(e)),
+        };
+
+        for output_idx in 0.. {
+            let output = match adapter.EnumOutputs(output_idx) {
+                Ok(output) => output,
+                Err(e) if e.code() == DXGI_ERROR_NOT_FOUND => break,
+                Err(e) => return Err(SysError::DxgiQueryFailed(e)),
+            };
+
+            // IDXGIOutput6 is required for GetDesc1, outputs which don't support it can't be HDR
+            let Ok(output) = output.cast::<IDXGIOutput6>() else {
+                continue;
+            };
+
+            let desc = output.GetDesc1().map_err(SysError::DxgiQueryFailed)?;
+            if wchar_to_string(&desc.DeviceName) == gdi_name {
+                return Ok(Some(desc));
+            }
+        }
+    }
+
+    Ok(None)
+}
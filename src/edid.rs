@@ -0,0 +1,613 @@
+use std::iter::once;
+
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::System::Registry::RegGetValueW;
+use windows::Win32::System::Registry::HKEY_LOCAL_MACHINE;
+use windows::Win32::System::Registry::RRF_RT_REG_BINARY;
+
+use crate::error::SysError;
+
+/// Size of the base EDID block and of every extension block that follows it
+pub const EDID_BLOCK_SIZE: usize = 128;
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const SERIAL_NUMBER_TAG: u8 = 0xFF;
+const DESCRIPTOR_OFFSETS: [usize; 4] = [54, 72, 90, 108];
+const DESCRIPTOR_SIZE: usize = 18;
+const RANGE_LIMITS_TAG: u8 = 0xFD;
+const TEXT_DESCRIPTOR_TAGS: [u8; 3] = [0xFF, 0xFE, 0xFC];
+const MODEL_YEAR_WEEK: u8 = 0xFF;
+const MANUFACTURE_YEAR_BASE: u16 = 1990;
+
+const CTA_EXTENSION_TAG: u8 = 0x02;
+const CTA_AUDIO_TAG: u8 = 0x01;
+const CTA_EXTENDED_TAG: u8 = 0x07;
+const CTA_HDR_STATIC_METADATA_TAG: u8 = 0x06;
+
+/// The identity of a monitor from its base EDID block, which (unlike `Device::device_description`)
+/// can tell apart two monitors of the same model
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EdidInfo {
+    /// The 3-letter PnP manufacturer ID, e.g. `DEL`
+    pub manufacturer_id: String,
+    pub product_code: u16,
+    /// The numeric serial number, or `None` if the monitor leaves it blank
+    pub serial_number: Option<u32>,
+    /// The serial number from the display descriptor with tag `0xFF`, if present
+    pub serial_string: Option<String>,
+    /// The week of manufacture (1-54), or `None` if the monitor leaves it unspecified
+    pub manufacture_week: Option<u8>,
+    /// The year of manufacture, or the model year if the monitor declares that instead (a week of
+    /// `0xFF` in EDID 1.4)
+    pub manufacture_year: Option<u16>,
+}
+
+/// HDR static metadata as declared in a CTA-861.3 HDR Static Metadata Data Block.\
+/// Luminance values are in nits (cd/m²) and are `None` when the optional byte is not present.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HdrStaticMetadata {
+    /// Bitmask of supported EOTFs (bit 0: SDR, bit 1: traditional HDR, bit 2: PQ, bit 3: HLG)
+    pub eotfs: u8,
+    /// Bitmask of supported static metadata descriptors (bit 0: Static Metadata Type 1)
+    pub metadata_descriptors: u8,
+    /// Desired content max luminance, i.e. the panel's declared peak luminance
+    pub max_luminance: Option<f32>,
+    /// Desired content max frame-average luminance
+    pub max_frame_average_luminance: Option<f32>,
+    /// Desired content min luminance
+    pub min_luminance: Option<f32>,
+}
+
+/// Frequency ranges as declared in the Display Range Limits descriptor (tag `0xFD`) of the base
+/// EDID block.\
+/// Rates are `u16` because EDID 1.4 allows offsetting them by 255 for monitors above 255 Hz/kHz.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrequencyRanges {
+    /// Minimum and maximum vertical (refresh) rate in Hz, i.e. the VRR window on adaptive sync
+    /// monitors
+    pub v_hz: (u16, u16),
+    /// Minimum and maximum horizontal rate in kHz
+    pub h_khz: (u16, u16),
+    /// Maximum pixel clock in MHz, or `None` if the monitor doesn't declare one
+    pub max_pixel_clock_mhz: Option<u16>,
+}
+
+/// The audio format of a CTA-861 Short Audio Descriptor
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AudioCodec {
+    Lpcm,
+    Ac3,
+    Mpeg1,
+    Mp3,
+    Mpeg2,
+    AacLc,
+    Dts,
+    Atrac,
+    OneBitAudio,
+    EnhancedAc3,
+    DtsHd,
+    MatMlp,
+    Dst,
+    WmaPro,
+    /// A reserved or extended (format code 15) format
+    Other(u8),
+}
+
+impl From<u8> for AudioCodec {
+    fn from(code: u8) -> Self {
+        match code {
+            1 => Self::Lpcm,
+            2 => Self::Ac3,
+            3 => Self::Mpeg1,
+            4 => Self::Mp3,
+            5 => Self::Mpeg2,
+            6 => Self::AacLc,
+            7 => Self::Dts,
+            8 => Self::Atrac,
+            9 => Self::OneBitAudio,
+            10 => Self::EnhancedAc3,
+            11 => Self::DtsHd,
+            12 => Self::MatMlp,
+            13 => Self::Dst,
+            14 => Self::WmaPro,
+            code => Self::Other(code),
+        }
+    }
+}
+
+/// An audio format as declared in a CTA-861 Short Audio Descriptor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioFormat {
+    pub codec: AudioCodec,
+    pub max_channels: u8,
+    /// Supported sample rates in Hz
+    pub sample_rates: Vec<u32>,
+    /// Supported bit depths, only declared for `AudioCodec::Lpcm`
+    pub bit_depths: Vec<u8>,
+    /// Maximum bitrate in kbps, only declared for `AudioCodec::Ac3` through `AudioCodec::Atrac`
+    pub max_bitrate_kbps: Option<u32>,
+}
+
+/// Reads the full EDID (base block and all extension blocks) for a monitor from the registry.\
+/// The device path is expected in the "DOS Device Path" format returned by
+/// `EnumDisplayDevicesW` with `EDD_GET_DEVICE_INTERFACE_NAME`, e.g.
+/// `\\?\DISPLAY#DEL4109#5&2a9c5b3f&0&UID4353#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}`.\
+/// A `None` value means the monitor does not publish an EDID (e.g. virtual displays).
+pub(crate) unsafe fn read_edid(device_path: &str) -> Result<Option<Vec<u8>>, SysError> {
+    let Some(subkey) = registry_key_for_device_path(device_path) else {
+        return Ok(None);
+    };
+    let subkey = subkey.encode_utf16().chain(once(0)).collect::<Vec<_>>();
+    let value = "EDID".encode_utf16().chain(once(0)).collect::<Vec<_>>();
+
+    let mut size = 0u32;
+    match RegGetValueW(
+        HKEY_LOCAL_MACHINE,
+        PCWSTR(subkey.as_ptr()),
+        PCWSTR(value.as_ptr()),
+        RRF_RT_REG_BINARY,
+        None,
+        None,
+        Some(&mut size),
+    ) {
+        ERROR_SUCCESS => {}
+        ERROR_FILE_NOT_FOUND => return Ok(None),
+        e => {
+            return Err(SysError::ReadingEdidFailed {
+                device_path: device_path.to_string(),
+                source: e.into(),
+            })
+        }
+    }
+
+    let mut edid = vec![0u8; size as usize];
+    match RegGetValueW(
+        HKEY_LOCAL_MACHINE,
+        PCWSTR(subkey.as_ptr()),
+        PCWSTR(value.as_ptr()),
+        RRF_RT_REG_BINARY,
+        None,
+        Some(edid.as_mut_ptr().cast()),
+        Some(&mut size),
+    ) {
+        ERROR_SUCCESS => {}
+        ERROR_FILE_NOT_FOUND => return Ok(None),
+        e => {
+            return Err(SysError::ReadingEdidFailed {
+                device_path: device_path.to_string(),
+                source: e.into(),
+            })
+        }
+    }
+    edid.truncate(size as usize);
+
+    Ok((edid.len() >= EDID_BLOCK_SIZE).then_some(edid))
+}
+
+/// Converts a DOS device path into the registry key holding the monitor's device parameters.\
+/// `\\?\DISPLAY#DEL4109#5&2a9c5b3f&0&UID4353#{guid}` becomes
+/// `SYSTEM\CurrentControlSet\Enum\DISPLAY\DEL4109\5&2a9c5b3f&0&UID4353\Device Parameters`
+fn registry_key_for_device_path(device_path: &str) -> Option<String> {
+    let mut parts = device_path.trim_start_matches(r"\\?\").split('#');
+    let enumerator = parts.next().filter(|p| !p.is_empty())?;
+    let hardware_id = parts.next().filter(|p| !p.is_empty())?;
+    let instance_id = parts.next().filter(|p| !p.is_empty())?;
+    Some(format!(
+        r"SYSTEM\CurrentControlSet\Enum\{enumerator}\{hardware_id}\{instance_id}\Device Parameters"
+    ))
+}
+
+/// Returns the bytes of every extension block following the base 128 byte EDID block
+pub fn extension_blocks(edid: &[u8]) -> &[u8] {
+    edid.get(EDID_BLOCK_SIZE..).unwrap_or_default()
+}
+
+/// Parses the manufacturer, product code and serial numbers from the base EDID block.\
+/// A `None` value means the data does not start with the EDID header.
+pub fn parse_edid_info(edid: &[u8]) -> Option<EdidInfo> {
+    if edid.get(..EDID_HEADER.len())? != EDID_HEADER || edid.len() < EDID_BLOCK_SIZE {
+        return None;
+    }
+
+    // The manufacturer ID is three 5-bit letters packed big-endian into bytes 8-9, where 1 is 'A'
+    let packed = u16::from_be_bytes([edid[8], edid[9]]);
+    let manufacturer_id = [10, 5, 0]
+        .iter()
+        .map(|shift| char::from(b'A' - 1 + ((packed >> shift) & 0x1F) as u8))
+        .collect();
+
+    let serial_number = u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]);
+    let serial_string = display_descriptors(edid)
+        .find(|d| d[3] == SERIAL_NUMBER_TAG)
+        .map(descriptor_text)
+        .filter(|text| !text.is_empty());
+
+    // Byte 16 is the week, where 0 is unspecified and 0xFF marks byte 17 as the model year, which
+    // is stored as an offset from 1990 like the year of manufacture
+    let week = edid[16];
+
+    Some(EdidInfo {
+        manufacturer_id,
+        product_code: u16::from_le_bytes([edid[10], edid[11]]),
+        serial_number: (serial_number != 0).then_some(serial_number),
+        serial_string,
+        manufacture_week: (week != 0 && week != MODEL_YEAR_WEEK).then_some(week),
+        manufacture_year: Some(MANUFACTURE_YEAR_BASE + u16::from(edid[17])),
+    })
+}
+
+/// Parses the `(width, height)` in millimeters from the basic display parameters (bytes 21-22,
+/// in centimeters) of the base EDID block.\
+/// A `None` value means the size is undefined, which is also the case when the bytes encode an
+/// aspect ratio instead (one of them zero).
+pub fn parse_physical_size_mm(edid: &[u8]) -> Option<(u32, u32)> {
+    match edid.get(21..23)? {
+        &[width, height] if width != 0 && height != 0 => {
+            Some((u32::from(width) * 10, u32::from(height) * 10))
+        }
+        _ => None,
+    }
+}
+
+/// Iterates over the display descriptors (as opposed to detailed timing descriptors) of the base
+/// EDID block, yielding the full 18 bytes with the tag at index 3
+fn display_descriptors(edid: &[u8]) -> impl Iterator<Item = &[u8]> {
+    DESCRIPTOR_OFFSETS.iter().filter_map(|&offset| {
+        let descriptor = edid.get(offset..offset + DESCRIPTOR_SIZE)?;
+        // Display descriptors start with a zero pixel clock, which sets them apart from detailed
+        // timing descriptors
+        (descriptor[..3] == [0, 0, 0]).then_some(descriptor)
+    })
+}
+
+/// Returns the strings of the text display descriptors in the base EDID block in the order they
+/// appear: serial numbers (tag `0xFF`), data strings (tag `0xFE`) and product names (tag `0xFC`)
+pub fn parse_text_descriptors(edid: &[u8]) -> Vec<String> {
+    display_descriptors(edid)
+        .filter(|d| TEXT_DESCRIPTOR_TAGS.contains(&d[3]))
+        .map(descriptor_text)
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+/// Reads the string of a text display descriptor, which is terminated by a line feed and padded
+/// with spaces
+fn descriptor_text(descriptor: &[u8]) -> String {
+    let text = &descriptor[5..];
+    let end = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+    String::from_utf8_lossy(&text[..end]).trim_end().to_string()
+}
+
+/// Parses the Display Range Limits descriptor from the base EDID block
+pub fn parse_frequency_ranges(edid: &[u8]) -> Option<FrequencyRanges> {
+    let descriptor = display_descriptors(edid).find(|d| d[3] == RANGE_LIMITS_TAG)?;
+
+    // Byte 4 holds the EDID 1.4 rate offset flags: bits 1:0 for vertical and bits 3:2 for
+    // horizontal, where 0b10 offsets the maximum and 0b11 offsets both
+    let flags = descriptor[4];
+    let range = |min: u8, max: u8, flags: u8| {
+        let max_offset = if flags & 0b10 != 0 { 255 } else { 0 };
+        let min_offset = if flags & 0b11 == 0b11 { 255 } else { 0 };
+        (u16::from(min) + min_offset, u16::from(max) + max_offset)
+    };
+
+    Some(FrequencyRanges {
+        v_hz: range(descriptor[5], descriptor[6], flags & 0b11),
+        h_khz: range(descriptor[7], descriptor[8], (flags >> 2) & 0b11),
+        max_pixel_clock_mhz: (descriptor[9] != 0).then(|| u16::from(descriptor[9]) * 10),
+    })
+}
+
+/// Iterates over the data blocks of every CTA-861 extension block, yielding `(tag, payload)`.\
+/// For extended tag blocks (tag `0x07`) the payload still begins with the extended tag code.
+pub(crate) fn cta_data_blocks(extensions: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    extensions
+        .chunks_exact(EDID_BLOCK_SIZE)
+        .filter(|block| block[0] == CTA_EXTENSION_TAG)
+        .flat_map(|block| {
+            // Byte 2 is the offset of the first detailed timing descriptor, which is also where
+            // the data block collection (starting at byte 4) ends
+            let end = usize::from(block[2]).clamp(4, EDID_BLOCK_SIZE - 1);
+            let mut collection = &block[4..end];
+            std::iter::from_fn(move || {
+                let (&header, rest) = collection.split_first()?;
+                let len = usize::from(header & 0x1f);
+                let payload = rest.get(..len)?;
+                collection = &rest[len..];
+                Some((header >> 5, payload))
+            })
+        })
+}
+
+/// Parses the Short Audio Descriptors of every CTA-861 Audio Data Block in the EDID extension
+/// blocks
+pub fn parse_audio_formats(extensions: &[u8]) -> Vec<AudioFormat> {
+    const SAMPLE_RATES: [u32; 7] = [32_000, 44_100, 48_000, 88_200, 96_000, 176_400, 192_000];
+    const BIT_DEPTHS: [u8; 3] = [16, 20, 24];
+
+    cta_data_blocks(extensions)
+        .filter(|(tag, _)| *tag == CTA_AUDIO_TAG)
+        .flat_map(|(_, payload)| payload.chunks_exact(3))
+        .map(|sad| {
+            let codec = AudioCodec::from((sad[0] >> 3) & 0x0f);
+            AudioFormat {
+                codec,
+                max_channels: (sad[0] & 0x07) + 1,
+                sample_rates: select_flags(sad[1], &SAMPLE_RATES),
+                bit_depths: match codec {
+                    AudioCodec::Lpcm => select_flags(sad[2], &BIT_DEPTHS),
+                    _ => Vec::new(),
+                },
+                max_bitrate_kbps: match (sad[0] >> 3) & 0x0f {
+                    2..=8 => Some(u32::from(sad[2]) * 8),
+                    _ => None,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Returns the values whose bit is set in `bits`, where bit 0 corresponds to `values[0]`
+fn select_flags<T: Copy>(bits: u8, values: &[T]) -> Vec<T> {
+    values
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| bits & (1 << idx) != 0)
+        .map(|(_, &value)| value)
+        .collect()
+}
+
+/// Parses the CTA-861.3 HDR Static Metadata Data Block from the EDID extension blocks
+pub fn parse_hdr_static_metadata(extensions: &[u8]) -> Option<HdrStaticMetadata> {
+    let payload = cta_data_blocks(extensions).find_map(|(tag, payload)| {
+        (tag == CTA_EXTENDED_TAG && payload.first() == Some(&CTA_HDR_STATIC_METADATA_TAG))
+            .then(|| &payload[1..])
+    })?;
+
+    let luminance = |cv: u8| 50.0 * 2f32.powf(f32::from(cv) / 32.0);
+    let max_luminance = payload.get(2).copied().map(luminance);
+    let max_frame_average_luminance = payload.get(3).copied().map(luminance);
+    let min_luminance = payload
+        .get(4)
+        .and_then(|&cv| max_luminance.map(|max| max * (f32::from(cv) / 255.0).powi(2) / 100.0));
+
+    Some(HdrStaticMetadata {
+        eotfs: payload.first().copied().unwrap_or_default(),
+        metadata_descriptors: payload.get(1).copied().unwrap_or_default(),
+        max_luminance,
+        max_frame_average_luminance,
+        min_luminance,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A base EDID block modelled on a Dell U2415, with a serial number descriptor at offset 72
+    const DELL_U2415: [u8; EDID_BLOCK_SIZE] = [
+        0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x10, 0xAC, 0xBA, 0xA0, 0x4C, 0x4A, 0x33,
+        0x30, 0x2A, 0x19, 0x01, 0x04, 0xA5, 0x34, 0x20, 0x78, 0x3A, 0xEE, 0x95, 0xA3, 0x54, 0x4C,
+        0x99, 0x26, 0x0F, 0x50, 0x54, 0xA5, 0x4B, 0x00, 0x71, 0x4F, 0x81, 0x80, 0xA9, 0x40, 0xD1,
+        0xC0, 0xD1, 0x00, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x28, 0x3C, 0x80, 0xA0, 0x70, 0xB0,
+        0x23, 0x40, 0x30, 0x20, 0x36, 0x00, 0x06, 0x44, 0x21, 0x00, 0x00, 0x1A, 0x00, 0x00, 0x00,
+        0xFF, 0x00, 0x43, 0x46, 0x56, 0x39, 0x4E, 0x35, 0x35, 0x47, 0x30, 0x4C, 0x50, 0x4C, 0x0A,
+        0x00, 0x00, 0x00, 0xFC, 0x00, 0x44, 0x45, 0x4C, 0x4C, 0x20, 0x55, 0x32, 0x34, 0x31, 0x35,
+        0x0A, 0x20, 0x20, 0x00, 0x00, 0x00, 0xFD, 0x00, 0x38, 0x4C, 0x1E, 0x51, 0x11, 0x00, 0x0A,
+        0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x0D,
+    ];
+
+    #[test]
+    fn parses_manufacturer_and_serial() {
+        let info = parse_edid_info(&DELL_U2415).unwrap();
+
+        assert_eq!(info.manufacturer_id, "DEL");
+        assert_eq!(info.product_code, 0xA0BA);
+        assert_eq!(info.serial_number, Some(0x3033_4A4C));
+        assert_eq!(info.serial_string.as_deref(), Some("CFV9N55G0LPL"));
+    }
+
+    #[test]
+    fn zero_serial_number_is_none() {
+        let mut edid = DELL_U2415;
+        edid[12..16].fill(0);
+
+        assert_eq!(parse_edid_info(&edid).unwrap().serial_number, None);
+    }
+
+    #[test]
+    fn parses_manufacture_week_and_year() {
+        let info = parse_edid_info(&DELL_U2415).unwrap();
+
+        assert_eq!(info.manufacture_week, Some(42));
+        assert_eq!(info.manufacture_year, Some(2015));
+    }
+
+    #[test]
+    fn unspecified_week_is_none() {
+        let mut edid = DELL_U2415;
+        edid[16] = 0;
+        let info = parse_edid_info(&edid).unwrap();
+
+        assert_eq!(info.manufacture_week, None);
+        assert_eq!(info.manufacture_year, Some(2015));
+    }
+
+    #[test]
+    fn model_year_week_keeps_the_year() {
+        let mut edid = DELL_U2415;
+        edid[16] = MODEL_YEAR_WEEK;
+        edid[17] = 0x22;
+        let info = parse_edid_info(&edid).unwrap();
+
+        assert_eq!(info.manufacture_week, None);
+        assert_eq!(info.manufacture_year, Some(2024));
+    }
+
+    /// Builds a CTA-861 extension block holding `data_blocks`, followed directly by the (empty)
+    /// detailed timing descriptors
+    fn cta_extension(data_blocks: &[u8]) -> [u8; EDID_BLOCK_SIZE] {
+        let mut block = [0u8; EDID_BLOCK_SIZE];
+        block[0] = CTA_EXTENSION_TAG;
+        block[1] = 0x03;
+        block[2] = 4 + data_blocks.len() as u8;
+        block[4..4 + data_blocks.len()].copy_from_slice(data_blocks);
+        block[127] = block[..127]
+            .iter()
+            .fold(0u8, |sum, &b| sum.wrapping_add(b))
+            .wrapping_neg();
+        block
+    }
+
+    fn assert_nits(actual: Option<f32>, expected: f32) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 0.001, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn parses_hdr_static_metadata() {
+        // Extended tag block (tag 7, 6 bytes) with the HDR Static Metadata extended tag (6)
+        let extension = cta_extension(&[0xE6, 0x06, 0x0D, 0x01, 0x60, 0x40, 0x10]);
+        let hdr = parse_hdr_static_metadata(&extension).unwrap();
+
+        // SDR, PQ and HLG, but not traditional HDR
+        assert_eq!(hdr.eotfs, 0b1101);
+        assert_eq!(hdr.metadata_descriptors, 0x01);
+        assert_nits(hdr.max_luminance, 400.0);
+        assert_nits(hdr.max_frame_average_luminance, 200.0);
+        // 400 * (16 / 255)^2 / 100
+        assert_nits(hdr.min_luminance, 0.015_748);
+    }
+
+    #[test]
+    fn hdr_static_metadata_without_luminance() {
+        let extension = cta_extension(&[0xE3, 0x06, 0x05, 0x01]);
+        let hdr = parse_hdr_static_metadata(&extension).unwrap();
+
+        assert_eq!(hdr.eotfs, 0b0101);
+        assert_eq!(hdr.max_luminance, None);
+        assert_eq!(hdr.max_frame_average_luminance, None);
+        assert_eq!(hdr.min_luminance, None);
+    }
+
+    #[test]
+    fn truncated_hdr_static_metadata_is_none() {
+        // The header declares 6 bytes, but the data block collection ends after 2
+        let extension = cta_extension(&[0xE6, 0x06, 0x0D]);
+        assert_eq!(parse_hdr_static_metadata(&extension), None);
+
+        // A block of another extended tag (5, Colorimetry) is not HDR static metadata
+        let extension = cta_extension(&[0xE3, 0x05, 0xC0, 0x00]);
+        assert_eq!(parse_hdr_static_metadata(&extension), None);
+
+        assert_eq!(parse_hdr_static_metadata(&[]), None);
+    }
+
+    #[test]
+    fn parses_frequency_ranges() {
+        let ranges = parse_frequency_ranges(&DELL_U2415).unwrap();
+
+        assert_eq!(ranges.v_hz, (56, 76));
+        assert_eq!(ranges.h_khz, (30, 81));
+        assert_eq!(ranges.max_pixel_clock_mhz, Some(170));
+    }
+
+    #[test]
+    fn frequency_ranges_apply_rate_offsets() {
+        let mut edid = DELL_U2415;
+        // The range limits descriptor is the last one. Offset both vertical rates (0b11) and only
+        // the maximum horizontal rate (0b10)
+        edid[DESCRIPTOR_OFFSETS[3] + 4] = 0b1011;
+        edid[DESCRIPTOR_OFFSETS[3] + 9] = 0;
+        let ranges = parse_frequency_ranges(&edid).unwrap();
+
+        assert_eq!(ranges.v_hz, (56 + 255, 76 + 255));
+        assert_eq!(ranges.h_khz, (30, 81 + 255));
+        assert_eq!(ranges.max_pixel_clock_mhz, None);
+    }
+
+    #[test]
+    fn parses_audio_formats() {
+        // An Audio Data Block (tag 1, 6 bytes) with two Short Audio Descriptors: 2 channel LPCM at
+        // 32/44.1/48 kHz in 16/20/24 bit, and 6 channel AC-3 at 48 kHz up to 640 kbps
+        let extension = cta_extension(&[0x26, 0x09, 0x07, 0x07, 0x15, 0x04, 0x50]);
+        let formats = parse_audio_formats(&extension);
+
+        assert_eq!(
+            formats,
+            [
+                AudioFormat {
+                    codec: AudioCodec::Lpcm,
+                    max_channels: 2,
+                    sample_rates: vec![32_000, 44_100, 48_000],
+                    bit_depths: vec![16, 20, 24],
+                    max_bitrate_kbps: None,
+                },
+                AudioFormat {
+                    codec: AudioCodec::Ac3,
+                    max_channels: 6,
+                    sample_rates: vec![48_000],
+                    bit_depths: vec![],
+                    max_bitrate_kbps: Some(640),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn audio_formats_skip_other_data_blocks() {
+        // A Video Data Block (tag 2) before an Audio Data Block with an extended format code (15)
+        let extension = cta_extension(&[0x42, 0x10, 0x04, 0x23, 0x7F, 0x7F, 0x00]);
+        let formats = parse_audio_formats(&extension);
+
+        assert_eq!(formats.len(), 1);
+        assert_eq!(formats[0].codec, AudioCodec::Other(15));
+        assert_eq!(formats[0].max_channels, 8);
+        assert_eq!(formats[0].sample_rates.len(), 7);
+        assert_eq!(formats[0].max_bitrate_kbps, None);
+
+        assert!(parse_audio_formats(&[]).is_empty());
+    }
+
+    #[test]
+    fn parses_text_descriptors() {
+        assert_eq!(
+            parse_text_descriptors(&DELL_U2415),
+            ["CFV9N55G0LPL", "DELL U2415"]
+        );
+    }
+
+    #[test]
+    fn text_descriptors_trim_terminator_and_padding() {
+        let mut edid = DELL_U2415;
+        // Replace the range limits descriptor with a data string (tag 0xFE), padded after the
+        // line feed, and blank the product name (tag 0xFC) down to its terminator
+        let data_string = DESCRIPTOR_OFFSETS[3];
+        edid[data_string + 3] = 0xFE;
+        edid[data_string + 4] = 0;
+        edid[data_string + 5..data_string + DESCRIPTOR_SIZE].copy_from_slice(b"ABC 123  \n   ");
+        let product_name = DESCRIPTOR_OFFSETS[2];
+        edid[product_name + 5..product_name + DESCRIPTOR_SIZE].copy_from_slice(b"\n            ");
+
+        assert_eq!(parse_text_descriptors(&edid), ["CFV9N55G0LPL", "ABC 123"]);
+    }
+
+    #[test]
+    fn parses_physical_size_in_millimeters() {
+        // 52 x 32 cm
+        assert_eq!(parse_physical_size_mm(&DELL_U2415), Some((520, 320)));
+    }
+
+    #[test]
+    fn undefined_physical_size_is_none() {
+        // Projectors leave both bytes zero, and a single zero byte encodes an aspect ratio
+        let mut edid = DELL_U2415;
+        edid[21..23].fill(0);
+        assert_eq!(parse_physical_size_mm(&edid), None);
+
+        edid[21] = 0x4F;
+        assert_eq!(parse_physical_size_mm(&edid), None);
+    }
+}
@@ -4,14 +4,83 @@ use std::error::Error as StdError;
 
 use thiserror::Error;
 use windows::core::Error as WinError;
+use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+use windows::Win32::Foundation::E_ACCESSDENIED;
 
 /// Errors used in this API
+///
+/// Each variant is a kind of failure a caller may want to handle differently, e.g. to decide
+/// whether to retry. There is no variant wrapping a bare `windows::core::Error`: every variant
+/// keeps the Win32 error in its source chain instead, and `Error::win32_code` returns its HRESULT.
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum Error {
     /// Getting a list of brightness devices failed
     #[error("Failed to list brightness devices")]
     ListingDevicesFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// A Win32 call was denied access, e.g. in restricted or Remote Desktop sessions.\
+    /// This takes precedence over the variant for the operation which failed (e.g. `DdcCiFailed`
+    /// or `ConfiguringDisplaysFailed`), as retrying will not help.
+    #[error("Access was denied")]
+    AccessDenied(#[source] Box<dyn StdError + Send + Sync>),
+    /// The monitors returned by different Win32 APIs did not match up, because monitors were
+    /// connected or disconnected during the enumeration, so enumerating again is likely to succeed
+    #[error("The monitors changed during the enumeration")]
+    EnumerationRace(#[source] Box<dyn StdError + Send + Sync>),
+    /// A display device has no matching `DISPLAYCONFIG` data, e.g. because a monitor was connected
+    /// during the enumeration, or because the data can't be read in this session
+    #[error("The display config data of a device is missing")]
+    DeviceInfoMissing(#[source] Box<dyn StdError + Send + Sync>),
+    /// Reading the EDID of a device failed
+    #[error("Failed to read EDID")]
+    ReadingEdidFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// Starting a watcher for display events failed
+    #[error("Failed to start watching for display events")]
+    WatcherFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// Querying additional information about a device failed
+    #[error("Failed to query device information")]
+    QueryingDeviceFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// Getting or setting the wallpaper of a device failed
+    #[error("Failed to get or set wallpaper")]
+    WallpaperFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// Communicating with a monitor over DDC/CI failed
+    #[error("Failed to communicate with the monitor over DDC/CI")]
+    DdcCiFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// Writing a VCP feature over DDC/CI failed, e.g. because the monitor does not accept changes
+    /// to it
+    #[error("Failed to write to the monitor over DDC/CI")]
+    DdcCiWriteFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// Changing the display configuration failed
+    #[error("Failed to change the display configuration")]
+    ConfiguringDisplaysFailed(#[source] Box<dyn StdError + Send + Sync>),
+    /// A stored `HMONITOR` no longer refers to a monitor, so the displays need to be enumerated
+    /// again
+    #[error("The monitor handle is stale")]
+    MonitorHandleStale(#[source] Box<dyn StdError + Send + Sync>),
+    /// A stored device is no longer attached to its monitor, e.g. because it was unplugged, so it
+    /// should be dropped
+    #[error("The device was disconnected")]
+    DeviceDisconnected(#[source] Box<dyn StdError + Send + Sync>),
+    /// Capturing the contents of the screen failed
+    #[error("Failed to capture the screen")]
+    CaptureFailed(#[source] Box<dyn StdError + Send + Sync>),
+}
+
+impl Error {
+    /// Walks the source chain to the underlying `windows::core::Error` and returns its HRESULT,
+    /// e.g. to branch on `E_ACCESSDENIED` when deciding whether to retry.\
+    /// A `None` value means the error did not originate from a Win32 call.
+    #[must_use]
+    pub fn win32_code(&self) -> Option<i32> {
+        let mut source = StdError::source(self);
+        while let Some(e) = source {
+            if let Some(e) = e.downcast_ref::<WinError>() {
+                return Some(e.code().0);
+            }
+            source = e.source();
+        }
+        None
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -24,41 +93,186 @@ pub(crate) enum SysError {
     QueryDisplayConfigFailed(#[source] WinError),
     #[error("Failed to get display config device info")]
     DisplayConfigGetDeviceInfoFailed(#[source] WinError),
-    #[error("Failed to get monitor info")]
-    GetMonitorInfoFailed(#[source] WinError),
-    #[error("Failed to get physical monitors from the HMONITOR")]
-    GetPhysicalMonitorsFailed(#[source] WinError),
+    #[error("Failed to get monitor info for HMONITOR {hmonitor:#x}")]
+    GetMonitorInfoFailed { hmonitor: isize, source: WinError },
+    #[error("Failed to get the current display settings of {device_name}")]
+    EnumDisplaySettingsFailed { device_name: String },
+    #[error("Failed to get DPI for HMONITOR {hmonitor:#x} ({device_name})")]
+    GetDpiFailed {
+        hmonitor: isize,
+        device_name: String,
+        source: WinError,
+    },
+    #[error("Failed to get window rect")]
+    GetWindowRectFailed(#[source] WinError),
+    #[error("Failed to get physical monitors from HMONITOR {hmonitor:#x} ({device_name})")]
+    GetPhysicalMonitorsFailed {
+        hmonitor: isize,
+        device_name: String,
+        source: WinError,
+    },
     #[error(
-    "The length of GetPhysicalMonitorsFromHMONITOR() and EnumDisplayDevicesW() results did not \
-     match, this could be because monitors were connected/disconnected while loading devices"
+        "GetPhysicalMonitorsFromHMONITOR() returned {physical_monitors} and EnumDisplayDevicesW() \
+     returned {display_devices} results for HMONITOR {hmonitor:#x} ({device_name}) which could not \
+     be matched up, this could be because monitors were connected/disconnected while loading \
+     devices"
     )]
-    EnumerationMismatch,
+    EnumerationMismatch {
+        hmonitor: isize,
+        device_name: String,
+        physical_monitors: usize,
+        display_devices: usize,
+    },
     #[error(
     "Unable to find a matching device info for this display device, this could be because monitors \
      were connected while loading devices"
     )]
     DeviceInfoMissing,
+    #[error("The display uses an unsupported color encoding ({0})")]
+    UnsupportedColorEncoding(i32),
+    #[error("The HMONITOR was invalidated by a disconnect or display configuration change")]
+    MonitorHandleStale,
+    #[error("{device_path} is no longer attached to its HMONITOR")]
+    DeviceDisconnected { device_path: String },
+    #[error("No displays are connected")]
+    NoDisplays,
     #[error("Failed to open monitor interface handle (CreateFileW)")]
     OpeningMonitorDeviceInterfaceHandleFailed {
         device_name: String,
         source: WinError,
     },
+    #[error("Failed to read EDID from the registry for {device_path}")]
+    ReadingEdidFailed {
+        device_path: String,
+        source: WinError,
+    },
+    #[error("Failed to get the container ID of {device_path}")]
+    GettingContainerIdFailed {
+        device_path: String,
+        source: WinError,
+    },
+    #[error("Failed to install WinEvent hook")]
+    SetWinEventHookFailed(#[source] WinError),
+    #[error("Failed to create the watcher window")]
+    CreateWindowFailed(#[source] WinError),
+    #[error("The watcher thread exited before it finished starting")]
+    WatcherThreadFailed,
+    #[error("Failed to query DXGI outputs")]
+    DxgiQueryFailed(#[source] WinError),
+    #[error("Failed to get VCP feature {code:#04x}")]
+    GettingVcpFeatureFailed { code: u8, source: WinError },
+    #[error("Failed to set VCP feature {code:#04x}")]
+    SettingVcpFeatureFailed { code: u8, source: WinError },
+    #[error("The monitor does not support VCP feature {code:#04x}")]
+    VcpFeatureUnsupported { code: u8 },
+    #[error(
+        "The monitor replied to VCP feature {code:#04x} with only zeros, it may not support DDC/CI"
+    )]
+    VcpReplyEmpty { code: u8 },
+    #[error("Failed to get the DDC/CI capabilities of the monitor")]
+    GettingCapabilitiesFailed(#[source] WinError),
+    #[error("The monitor reported empty DDC/CI capabilities")]
+    CapabilitiesEmpty,
+    #[error("Failed to set display config")]
+    SetDisplayConfigFailed(#[source] WinError),
+    #[error("The topology does not contain whole DISPLAYCONFIG paths and modes")]
+    InvalidTopology,
+    #[error("Failed to query the user notification state")]
+    QueryUserNotificationStateFailed(#[source] WinError),
+    #[error("Failed to capture the screen with GDI")]
+    CaptureFailed(#[source] WinError),
+    #[error("Failed to create a device context for {device_name}")]
+    CreateDcFailed { device_name: String },
+    #[error("Failed to get the gamma ramp")]
+    GettingGammaRampFailed(#[source] WinError),
+    #[error("Failed to set the gamma ramp")]
+    SettingGammaRampFailed(#[source] WinError),
+    #[error("{device_name} is not an internal display, so its gamma ramp cannot be set")]
+    GammaRampUnsupported { device_name: String },
+    #[cfg(feature = "com")]
+    #[error("MMDevice API call failed")]
+    AudioEndpointFailed(#[source] WinError),
+    #[cfg(feature = "com")]
+    #[error("IDesktopWallpaper call failed")]
+    DesktopWallpaperFailed(#[source] WinError),
+    #[cfg(feature = "com")]
+    #[error("The wallpaper file {path} does not exist")]
+    WallpaperFileNotFound { path: String },
+    #[cfg(feature = "com")]
+    #[error("IDesktopWallpaper has no monitor matching {device_path}")]
+    WallpaperMonitorNotFound { device_path: String },
+}
+
+impl SysError {
+    /// Whether this error is caused by monitors being connected or disconnected during the
+    /// enumeration, in which case enumerating again is likely to succeed
+    pub(crate) const fn is_enumeration_race(&self) -> bool {
+        matches!(
+            self,
+            Self::EnumerationMismatch { .. } | Self::DeviceInfoMissing
+        )
+    }
 }
 
 impl From<SysError> for Error {
     fn from(e: SysError) -> Self {
+        if is_access_denied(&e) {
+            return Self::AccessDenied(Box::new(e));
+        }
+
         match &e {
-            SysError::EnumerationMismatch
-            | SysError::DeviceInfoMissing
+            SysError::EnumerationMismatch { .. } => Self::EnumerationRace(Box::new(e)),
+            SysError::DeviceInfoMissing => Self::DeviceInfoMissing(Box::new(e)),
+            SysError::NoDisplays
             | SysError::GetDisplayConfigBufferSizesFailed(..)
             | SysError::QueryDisplayConfigFailed(..)
             | SysError::DisplayConfigGetDeviceInfoFailed(..)
-            | SysError::GetPhysicalMonitorsFailed(..)
+            | SysError::GetPhysicalMonitorsFailed { .. }
             | SysError::EnumDisplayMonitorsFailed(..)
-            | SysError::GetMonitorInfoFailed(..)
+            | SysError::GetMonitorInfoFailed { .. }
+            | SysError::GetDpiFailed { .. }
+            | SysError::EnumDisplaySettingsFailed { .. }
+            | SysError::GetWindowRectFailed(..)
             | SysError::OpeningMonitorDeviceInterfaceHandleFailed { .. } => {
                 Self::ListingDevicesFailed(Box::new(e))
             }
+            SysError::ReadingEdidFailed { .. } => Self::ReadingEdidFailed(Box::new(e)),
+            SysError::SetWinEventHookFailed(..)
+            | SysError::CreateWindowFailed(..)
+            | SysError::WatcherThreadFailed => Self::WatcherFailed(Box::new(e)),
+            SysError::DxgiQueryFailed(..)
+            | SysError::UnsupportedColorEncoding(..)
+            | SysError::QueryUserNotificationStateFailed(..)
+            | SysError::CreateDcFailed { .. }
+            | SysError::GettingGammaRampFailed(..)
+            | SysError::GettingContainerIdFailed { .. } => Self::QueryingDeviceFailed(Box::new(e)),
+            SysError::GettingVcpFeatureFailed { .. }
+            | SysError::VcpFeatureUnsupported { .. }
+            | SysError::VcpReplyEmpty { .. }
+            | SysError::GettingCapabilitiesFailed(..)
+            | SysError::CapabilitiesEmpty => Self::DdcCiFailed(Box::new(e)),
+            SysError::SettingVcpFeatureFailed { .. } => Self::DdcCiWriteFailed(Box::new(e)),
+            SysError::SetDisplayConfigFailed(..)
+            | SysError::InvalidTopology
+            | SysError::SettingGammaRampFailed(..)
+            | SysError::GammaRampUnsupported { .. } => Self::ConfiguringDisplaysFailed(Box::new(e)),
+            SysError::MonitorHandleStale => Self::MonitorHandleStale(Box::new(e)),
+            SysError::DeviceDisconnected { .. } => Self::DeviceDisconnected(Box::new(e)),
+            SysError::CaptureFailed(..) => Self::CaptureFailed(Box::new(e)),
+            #[cfg(feature = "com")]
+            SysError::AudioEndpointFailed(..) => Self::QueryingDeviceFailed(Box::new(e)),
+            #[cfg(feature = "com")]
+            SysError::DesktopWallpaperFailed(..)
+            | SysError::WallpaperFileNotFound { .. }
+            | SysError::WallpaperMonitorNotFound { .. } => Self::WallpaperFailed(Box::new(e)),
         }
     }
 }
+
+/// Checks whether the `windows::core::Error` a `SysError` was caused by is an access denied error,
+/// either as an HRESULT or as a Win32 error code
+fn is_access_denied(e: &SysError) -> bool {
+    StdError::source(e)
+        .and_then(|source| source.downcast_ref::<WinError>())
+        .is_some_and(|e| e.code() == E_ACCESSDENIED || e.code() == ERROR_ACCESS_DENIED.to_hresult())
+}
@@ -0,0 +1,60 @@
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Foundation::RECT;
+use windows::Win32::Graphics::Gdi::MonitorFromWindow;
+use windows::Win32::Graphics::Gdi::MONITOR_DEFAULTTONULL;
+use windows::Win32::UI::Shell::SHQueryUserNotificationState;
+use windows::Win32::UI::Shell::QUNS_BUSY;
+use windows::Win32::UI::Shell::QUNS_RUNNING_D3D_FULL_SCREEN;
+use windows::Win32::UI::WindowsAndMessaging::GetClassNameW;
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+use windows::Win32::UI::WindowsAndMessaging::GetShellWindow;
+use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
+
+use crate::device::wchar_to_string;
+use crate::device::Device;
+use crate::device::MonitorHandle;
+use crate::error::Error;
+use crate::error::SysError;
+
+/// Window classes of the desktop itself, which covers every monitor without being fullscreen
+const DESKTOP_CLASSES: [&str; 2] = ["Progman", "WorkerW"];
+
+pub(crate) fn is_fullscreen_app_present() -> Result<bool, SysError> {
+    let state = unsafe { SHQueryUserNotificationState() }
+        .map_err(SysError::QueryUserNotificationStateFailed)?;
+    Ok(state == QUNS_BUSY || state == QUNS_RUNNING_D3D_FULL_SCREEN)
+}
+
+impl Device {
+    /// Checks whether the foreground window is on this monitor and covers all of it, e.g. a
+    /// borderless fullscreen game or video player.\
+    /// Unlike `is_fullscreen_app_present` this catches fullscreen windows which Windows does not
+    /// consider "busy", and tells which monitor they are on.
+    pub fn has_fullscreen_window(&self) -> Result<bool, Error> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_invalid() || hwnd == GetShellWindow() || is_desktop_window(hwnd) {
+                return Ok(false);
+            }
+
+            let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+            if MonitorHandle::from(hmonitor) != self.hmonitor {
+                return Ok(false);
+            }
+
+            let mut rect = RECT::default();
+            GetWindowRect(hwnd, &mut rect).map_err(SysError::GetWindowRectFailed)?;
+            Ok(rect.left <= self.size.left
+                && rect.top <= self.size.top
+                && rect.right >= self.size.right
+                && rect.bottom >= self.size.bottom)
+        }
+    }
+}
+
+unsafe fn is_desktop_window(hwnd: HWND) -> bool {
+    let mut class_name = [0u16; 16];
+    let len = GetClassNameW(hwnd, &mut class_name);
+    let class_name = wchar_to_string(&class_name[..len.max(0) as usize]);
+    DESKTOP_CLASSES.contains(&class_name.as_str())
+}
@@ -0,0 +1,85 @@
+use std::iter::once;
+
+use windows::core::w;
+use windows::core::PCWSTR;
+use windows::Win32::Graphics::Gdi::CreateDCW;
+use windows::Win32::Graphics::Gdi::DeleteDC;
+use windows::Win32::Graphics::Gdi::HDC;
+use windows::Win32::UI::ColorSystem::GetDeviceGammaRamp;
+use windows::Win32::UI::ColorSystem::SetDeviceGammaRamp;
+
+use crate::device::gdi_device_name;
+use crate::device::PhysicalDevice;
+use crate::error::Error;
+use crate::error::SysError;
+
+/// The red, green and blue lookup tables applied to the output of a display
+pub type GammaRamp = [[u16; 256]; 3];
+
+/// A device context for a single display, which is deleted on `Drop`
+struct DisplayDc(HDC);
+
+impl DisplayDc {
+    unsafe fn new(device_name: &str) -> Result<Self, SysError> {
+        let device = gdi_device_name(device_name)
+            .encode_utf16()
+            .chain(once(0))
+            .collect::<Vec<_>>();
+        let dc = CreateDCW(w!("DISPLAY"), PCWSTR(device.as_ptr()), PCWSTR::null(), None);
+        if dc.is_invalid() {
+            return Err(SysError::CreateDcFailed {
+                device_name: device_name.to_string(),
+            });
+        }
+        Ok(Self(dc))
+    }
+}
+
+impl Drop for DisplayDc {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = DeleteDC(self.0);
+        }
+    }
+}
+
+impl PhysicalDevice {
+    /// Reads the gamma ramp of this display with `GetDeviceGammaRamp`, as one lookup table per
+    /// red, green and blue channel.
+    pub fn get_gamma_ramp(&self) -> Result<GammaRamp, Error> {
+        unsafe {
+            let dc = DisplayDc::new(&self.device_name)?;
+            let mut ramp = [[0; 256]; 3];
+            if !GetDeviceGammaRamp(dc.0, ramp.as_mut_ptr().cast()).as_bool() {
+                return Err(
+                    SysError::GettingGammaRampFailed(windows::core::Error::from_win32()).into(),
+                );
+            }
+            Ok(ramp)
+        }
+    }
+
+    /// Applies a gamma ramp with `SetDeviceGammaRamp`, e.g. a curve reducing the blue channel for
+    /// a night-light effect. The ramp is reset by Windows when the display mode changes.\
+    /// This is only supported on internal displays (see `is_internal`), and fails with
+    /// `Error::ConfiguringDisplaysFailed` on external monitors. Windows also rejects ramps which
+    /// deviate too far from the identity ramp.
+    pub fn set_gamma_ramp(&self, ramp: &GammaRamp) -> Result<(), Error> {
+        if !self.is_internal() {
+            return Err(SysError::GammaRampUnsupported {
+                device_name: self.device_name.clone(),
+            }
+            .into());
+        }
+
+        unsafe {
+            let dc = DisplayDc::new(&self.device_name)?;
+            if !SetDeviceGammaRamp(dc.0, ramp.as_ptr().cast()).as_bool() {
+                return Err(
+                    SysError::SettingGammaRampFailed(windows::core::Error::from_win32()).into(),
+                );
+            }
+            Ok(())
+        }
+    }
+}
@@ -3,14 +3,352 @@
 // functionality, and all Linux-focused functionality, while retaining (and slightly modifying) the
 // "blocking" Windows code to retrieve detailed monitor display data for use in https://github.com/LGUG2Z/komorebi
 
+#[cfg(feature = "com")]
+mod audio;
+mod cache;
+mod capture;
+#[cfg(feature = "com")]
+mod com;
 mod device;
+mod diagnostics;
+mod dpi;
+mod dxgi;
+mod edid;
 pub mod error;
+mod fullscreen;
+mod gamma;
+mod output_technology;
+mod paths;
+mod query;
+mod topology;
+mod vcp;
+#[cfg(feature = "com")]
+mod wallpaper;
+mod watcher;
+
+use std::collections::HashMap;
+
+use windows::Win32::Foundation::RECT;
+
+pub use cache::DisplayCache;
+pub use capture::CapturedImage;
+pub use device::ColorFormat;
+pub use device::Device;
+pub use device::DisplayInfo;
+pub use device::DuplicateGroup;
+pub use device::Edge;
+pub use device::Geometry;
+pub use device::LayoutCell;
+pub use device::MonitorGroup;
+pub use device::MonitorHandle;
+pub use device::Orientation;
+pub use device::PhysicalDevice;
+pub use device::SignalMode;
+pub use device::WrappedFileHandle;
+pub use device::WrappedPhysicalMonitor;
+pub use diagnostics::diagnostic_report;
+pub use dpi::current_process_dpi_awareness;
+pub use dpi::recommended_dpi_awareness;
+pub use dpi::DpiAwareness;
+pub use dxgi::LuminanceRange;
+pub use edid::AudioCodec;
+pub use edid::AudioFormat;
+pub use edid::EdidInfo;
+pub use edid::FrequencyRanges;
+pub use edid::HdrStaticMetadata;
+pub use gamma::GammaRamp;
+pub use output_technology::output_technology_from_name;
+pub use output_technology::output_technology_name;
+pub use output_technology::ConnectorType;
+pub use paths::DisplayPath;
+pub use paths::PathScope;
+pub use query::DisplayQuery;
+pub use topology::Topology;
+pub use vcp::VcpReply;
+pub use watcher::DisplayEvent;
+pub use watcher::DisplayWatcher;
+pub use watcher::DpiChange;
+pub use watcher::DpiChangeWatcher;
+pub use watcher::ForegroundMonitorWatcher;
 
 pub fn connected_displays_physical(
 ) -> impl Iterator<Item = Result<device::PhysicalDevice, error::Error>> {
     device::connected_displays_physical().map(|r| r.map_err(Into::into))
 }
 
-pub fn connected_displays_all() -> impl Iterator<Item = Result<device::Device, error::Error>> {
+pub fn connected_displays_all() -> impl Iterator<Item = Result<device::Device, error::Error>> + Send
+{
     device::connected_displays_all().map(|r| r.map_err(Into::into))
 }
+
+/// Like `connected_displays_all`, but runs the enumeration on tokio's blocking thread pool, so
+/// that the blocking Win32 calls don't stall the async runtime (e.g. during bursts of hotplug
+/// events).
+#[cfg(feature = "tokio")]
+pub async fn connected_displays_all_async() -> Result<Vec<device::Device>, error::Error> {
+    tokio::task::spawn_blocking(|| connected_displays_all().collect())
+        .await
+        .map_err(|e| error::Error::ListingDevicesFailed(Box::new(e)))?
+}
+
+/// Like `connected_displays_physical`, but runs the enumeration on tokio's blocking thread pool,
+/// which includes opening the physical monitor and file handles.
+#[cfg(feature = "tokio")]
+pub async fn connected_displays_physical_async() -> Result<Vec<device::PhysicalDevice>, error::Error>
+{
+    tokio::task::spawn_blocking(|| connected_displays_physical().collect())
+        .await
+        .map_err(|e| error::Error::ListingDevicesFailed(Box::new(e)))?
+}
+
+/// Like `connected_displays_all`, but enumerates again (up to `max_attempts` times in total, with a
+/// short backoff) when the enumeration fails because monitors were connected or disconnected
+/// during it, e.g. while docking a laptop.\
+/// Any other error (e.g. `Error::AccessDenied`) is returned immediately.
+pub fn connected_displays_all_retry(
+    max_attempts: usize,
+) -> Result<Vec<device::Device>, error::Error> {
+    device::connected_displays_all_retry(max_attempts).map_err(Into::into)
+}
+
+/// Like `connected_displays_all`, followed by the monitors which are attached but disabled in the
+/// display settings (`Device::is_active` is `false`), e.g. to offer enabling them.\
+/// Inactive monitors are found by walking `EnumDisplayDevicesW` for every adapter, since they are
+/// not part of the desktop and so have no `HMONITOR`.\
+/// Active devices which fail to enumerate are skipped as in `connected_displays_all_lossy`, and
+/// the first error is only returned when none of them could be read.
+pub fn connected_displays_all_including_inactive() -> Result<Vec<device::Device>, error::Error> {
+    device::connected_displays_all_including_inactive().map_err(Into::into)
+}
+
+/// Enumerates every display it can, separating the successfully read devices from the errors.\
+/// Failures only affect the `HMONITOR` or display device they occurred on (e.g. a monitor which
+/// was disconnected mid-enumeration), so the devices of every other `HMONITOR` are still returned.
+/// Only a failure to list the `HMONITOR`s at all leaves the devices empty.
+pub fn connected_displays_all_lossy() -> (Vec<device::Device>, Vec<error::Error>) {
+    let (devices, errors) = device::connected_displays_all_lossy();
+    (devices, errors.into_iter().map(Into::into).collect())
+}
+
+/// Returns "the monitors a human would count": entries which share the same `size` or
+/// `device_path` (e.g. RDP virtual displays and "Duplicate" mode) are collapsed into one,
+/// preferring the entry which is not virtual.\
+/// Entries of adapters with `DISPLAY_DEVICE_MIRRORING_DRIVER` are skipped entirely.
+pub fn connected_displays_deduplicated() -> Result<Vec<device::Device>, error::Error> {
+    device::connected_displays_deduplicated().map_err(Into::into)
+}
+
+/// Returns every display keyed by its `device_path`.\
+/// If multiple devices report the same `device_path` (which should not happen, but mirror drivers
+/// may leave it empty), only the first enumerated device is kept.
+pub fn connected_displays_by_path() -> Result<HashMap<String, device::Device>, error::Error> {
+    device::connected_displays_by_path().map_err(Into::into)
+}
+
+/// Returns the groups of devices in "Duplicate" mode (those sharing an `HMONITOR`), each with the
+/// index of the primary source whose settings actually take effect.\
+/// Devices which are not part of a clone are not included.
+pub fn duplicate_groups() -> Result<Vec<device::DuplicateGroup>, error::Error> {
+    device::duplicate_groups().map_err(Into::into)
+}
+
+/// Returns the physical monitors grouped by the `HMONITOR` they show, in the order of
+/// `EnumDisplayMonitors`.\
+/// Unlike `connected_displays_physical`, monitors in "Duplicate" mode (which share one `HMONITOR`)
+/// are explicitly collected into the same group, rather than being returned as separate entries.
+pub fn grouped_displays() -> Result<Vec<device::MonitorGroup>, error::Error> {
+    device::grouped_displays().map_err(Into::into)
+}
+
+/// Cheaply checks whether a cached `HMONITOR` (e.g. `Device::hmonitor`) is still valid, without
+/// building a full `Device`.\
+/// Handles become stale when monitors are disconnected or the display configuration changes.
+pub fn is_hmonitor_valid(hmonitor: MonitorHandle) -> bool {
+    device::is_hmonitor_valid(hmonitor)
+}
+
+/// Returns the built-in laptop panel, i.e. the first display whose `ConnectorType` is `Internal`
+/// (see `Device::is_internal`), or `None` on desktops.\
+/// Enumeration stops at the first match, and no physical monitor or file handles are opened.
+pub fn internal_display() -> Result<Option<device::Device>, error::Error> {
+    device::internal_display().map_err(Into::into)
+}
+
+/// Returns every display which is not a built-in panel, e.g. the monitors of a docked laptop.\
+/// Displays with an unknown output technology are treated as external.
+pub fn external_displays() -> Result<Vec<device::Device>, error::Error> {
+    device::external_displays().map_err(Into::into)
+}
+
+/// Returns every display in a deterministic order, unlike `EnumDisplayMonitors`: the primary
+/// monitor first, then by `size.left` and `size.top`.\
+/// The order only changes when the arrangement does, so it can be used to refer to monitors by
+/// index across launches.
+pub fn connected_displays_all_sorted() -> Result<Vec<device::Device>, error::Error> {
+    device::connected_displays_all_sorted().map_err(Into::into)
+}
+
+/// Returns every display ordered by physical arrangement, left-to-right and then top-to-bottom,
+/// for "move to next monitor" style navigation with `Device::next_in` and `Device::prev_in`.
+pub fn displays_in_navigation_order() -> Result<Vec<device::Device>, error::Error> {
+    device::displays_in_navigation_order().map_err(Into::into)
+}
+
+/// Returns every display with its cell in a grid of the monitor arrangement, with the primary
+/// monitor at `(0, 0)`, for persisting a layout which survives resolution changes (e.g. "the
+/// monitor to the left of the primary" is always at column `-1`).\
+/// A monitor is one column (row) after another when it lies entirely to the right of (below) it.
+/// Monitors which overlap, or show the same desktop in "Duplicate" mode, share a cell. The result
+/// is ordered by row, then column, then position.
+pub fn layout() -> Result<Vec<(device::Device, LayoutCell)>, error::Error> {
+    device::layout().map_err(Into::into)
+}
+
+/// Returns every display a window intersects, ordered by intersection area (largest first).\
+/// This is useful for deciding how to split or reposition a window that spans multiple monitors.
+pub fn displays_under_window(hwnd: isize) -> Result<Vec<device::Device>, error::Error> {
+    device::displays_under_window(hwnd).map_err(Into::into)
+}
+
+/// Re-resolves a `Device` from a stored `HMONITOR` (e.g. `Device::hmonitor`).\
+/// A `None` value means the handle is stale, e.g. because the monitor was disconnected or the
+/// display configuration changed.\
+/// When multiple display devices share the `HMONITOR` (e.g. "Duplicate" mode) the first is returned.
+pub fn device_from_hmonitor(
+    hmonitor: MonitorHandle,
+) -> Result<Option<device::Device>, error::Error> {
+    unsafe { device::device_from_hmonitor(hmonitor.into()) }.map_err(Into::into)
+}
+
+/// Returns every display intersecting a rectangle in virtual desktop coordinates (e.g. a window's
+/// rectangle), in enumeration order.\
+/// The rectangle of every `HMONITOR` is intersected with the given one, and only monitors with a
+/// non-empty intersection are returned, so a rectangle which only touches the edge of a monitor
+/// does not count as intersecting it.
+pub fn displays_intersecting(rect: RECT) -> Result<Vec<device::Device>, error::Error> {
+    device::displays_intersecting(rect).map_err(Into::into)
+}
+
+/// Returns the display containing the screen point `(x, y)` in virtual desktop coordinates, via
+/// `MonitorFromPoint`.\
+/// A `None` value means the point is not on any display; it is not snapped to the nearest one.
+pub fn device_from_point(x: i32, y: i32) -> Result<Option<device::Device>, error::Error> {
+    device::device_from_point(x, y).map_err(Into::into)
+}
+
+/// Returns the display nearest to a rectangle in virtual desktop coordinates, e.g. to rescue a
+/// window whose saved position is now off-screen.\
+/// Like `MONITOR_DEFAULTTONEAREST`, the display with the largest overlap is preferred, and when
+/// the rectangle overlaps no display the one with the closest center is returned.
+pub fn nearest_display(
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+) -> Result<device::Device, error::Error> {
+    device::nearest_display(RECT {
+        left,
+        top,
+        right,
+        bottom,
+    })
+    .map_err(Into::into)
+}
+
+/// Returns the bounding rectangle of every monitor in virtual desktop coordinates, which starts at
+/// negative coordinates when a monitor is placed to the left of or above the primary monitor.\
+/// This may include areas not covered by any monitor in non-rectangular arrangements.
+pub fn virtual_screen_bounds() -> Result<RECT, error::Error> {
+    device::virtual_screen_bounds().map_err(Into::into)
+}
+
+/// Returns the total number of usable desktop pixels across all monitors (the sum of their work
+/// areas), counting monitors in "Duplicate" mode only once.
+pub fn total_work_area_pixels() -> Result<i64, error::Error> {
+    device::total_work_area_pixels().map_err(Into::into)
+}
+
+/// Returns the `DISPLAYCONFIG` paths in `scope`, each with whether it is active and whether a
+/// monitor is connected to its target.\
+/// With `PathScope::All` this includes the paths which could be activated, e.g. to find out which
+/// sources a connected but disabled monitor can be driven by.
+pub fn display_paths(scope: PathScope) -> Result<Vec<DisplayPath>, error::Error> {
+    paths::display_paths(scope).map_err(Into::into)
+}
+
+/// Captures the active display topology (every `DISPLAYCONFIG` path and mode), to be restored
+/// later with `apply_topology`.
+pub fn capture_topology() -> Result<Topology, error::Error> {
+    topology::capture_topology().map_err(Into::into)
+}
+
+/// Reapplies a topology captured by `capture_topology` in a single `SetDisplayConfig` call.\
+/// Displays which are not part of the topology are disabled.
+pub fn apply_topology(t: &Topology) -> Result<(), error::Error> {
+    topology::apply_topology(t).map_err(Into::into)
+}
+
+/// Checks whether Windows reports a fullscreen application, i.e. the user notification state is
+/// `QUNS_BUSY` (a fullscreen window) or `QUNS_RUNNING_D3D_FULL_SCREEN` (a fullscreen exclusive
+/// Direct3D application), so that overlays and notifications can hold back.\
+/// Use `Device::has_fullscreen_window` to find out which monitor is affected.
+pub fn is_fullscreen_app_present() -> Result<bool, error::Error> {
+    fullscreen::is_fullscreen_app_present().map_err(Into::into)
+}
+
+/// Captures every monitor into one image covering the bounding rectangle of the virtual desktop,
+/// with the gaps between monitors in non-rectangular arrangements left transparent.\
+/// Monitor positions are in the coordinate space of the calling thread's DPI awareness, so the
+/// process should be per-monitor DPI aware to get an unscaled capture of every monitor.
+pub fn capture_virtual_desktop() -> Result<CapturedImage, error::Error> {
+    capture::capture_virtual_desktop().map_err(Into::into)
+}
+
+/// Invokes `callback` whenever the display configuration changes (e.g. a monitor is connected or
+/// disconnected, or a laptop is docked), instead of polling `connected_displays_all`.\
+/// This is a shorthand for a `DisplayWatcher` which only reports `DisplayEvent::DisplaysChanged`,
+/// so bursts of change messages within 200ms result in a single call. The callback runs on the
+/// watcher's thread, which is stopped when the returned `DisplayWatcher` is dropped.
+pub fn watch_display_changes(
+    callback: impl Fn() + Send + 'static,
+) -> Result<DisplayWatcher, error::Error> {
+    DisplayWatcher::new(move |event| {
+        if matches!(event, DisplayEvent::DisplaysChanged) {
+            callback();
+        }
+    })
+}
+
+// Compile-time assertion that the iterator returned by `connected_displays_all` can be built on
+// one thread and consumed on another
+const _: () = {
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[allow(dead_code)]
+    fn connected_displays_all_is_send() {
+        assert_send(&connected_displays_all());
+    }
+
+    // Required to return physical devices from `connected_displays_physical_async`
+    #[allow(dead_code)]
+    fn physical_device_is_send(device: &PhysicalDevice) {
+        assert_send(device);
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(windows)]
+    #[test]
+    fn resolution_is_non_zero_for_a_connected_display() {
+        let devices = connected_displays_all()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert!(devices
+            .iter()
+            .any(|device| device.resolution.0 > 0 && device.resolution.1 > 0));
+    }
+}
@@ -0,0 +1,171 @@
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_COMPONENT_VIDEO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_COMPOSITE_VIDEO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EMBEDDED;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EXTERNAL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_USB_TUNNEL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DVI;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_D_JPN;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HD15;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HDMI;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_VIRTUAL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_WIRED;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_LVDS;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_MIRACAST;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_SDI;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_SDTVDONGLE;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_SVIDEO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_UDI_EMBEDDED;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_UDI_EXTERNAL;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY;
+
+/// A simplified, dependency-free view of `DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConnectorType {
+    /// `HD15`
+    Vga,
+    Dvi,
+    Hdmi,
+    /// `DISPLAYPORT_EXTERNAL`
+    DisplayPort,
+    /// `INTERNAL`, `LVDS`, `DISPLAYPORT_EMBEDDED` and `UDI_EMBEDDED`, i.e. a built-in panel
+    Internal,
+    /// `DISPLAYPORT_USB_TUNNEL` and `INDIRECT_WIRED` (e.g. DisplayLink)
+    Usb,
+    /// `MIRACAST`
+    Wireless,
+    /// Any other output technology, carrying the raw value so nothing is lost
+    Other(i32),
+}
+
+impl From<DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY> for ConnectorType {
+    fn from(t: DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY) -> Self {
+        match t {
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HD15 => Self::Vga,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DVI => Self::Dvi,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HDMI => Self::Hdmi,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EXTERNAL => Self::DisplayPort,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL
+            | DISPLAYCONFIG_OUTPUT_TECHNOLOGY_LVDS
+            | DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EMBEDDED
+            | DISPLAYCONFIG_OUTPUT_TECHNOLOGY_UDI_EMBEDDED => Self::Internal,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_USB_TUNNEL
+            | DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_WIRED => Self::Usb,
+            DISPLAYCONFIG_OUTPUT_TECHNOLOGY_MIRACAST => Self::Wireless,
+            DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY(raw) => Self::Other(raw),
+        }
+    }
+}
+
+const PREFIX: &str = "DISPLAYCONFIG_OUTPUT_TECHNOLOGY_";
+
+/// Every known output technology paired with its name, which is the constant name without the
+/// `DISPLAYCONFIG_OUTPUT_TECHNOLOGY_` prefix
+const OUTPUT_TECHNOLOGY_NAMES: [(DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY, &str); 20] = [
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER, "OTHER"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HD15, "HD15"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_SVIDEO, "SVIDEO"),
+    (
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_COMPOSITE_VIDEO,
+        "COMPOSITE_VIDEO",
+    ),
+    (
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_COMPONENT_VIDEO,
+        "COMPONENT_VIDEO",
+    ),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DVI, "DVI"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HDMI, "HDMI"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_LVDS, "LVDS"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_D_JPN, "D_JPN"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_SDI, "SDI"),
+    (
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EXTERNAL,
+        "DISPLAYPORT_EXTERNAL",
+    ),
+    (
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EMBEDDED,
+        "DISPLAYPORT_EMBEDDED",
+    ),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_UDI_EXTERNAL, "UDI_EXTERNAL"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_UDI_EMBEDDED, "UDI_EMBEDDED"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_SDTVDONGLE, "SDTVDONGLE"),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_MIRACAST, "MIRACAST"),
+    (
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_WIRED,
+        "INDIRECT_WIRED",
+    ),
+    (
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INDIRECT_VIRTUAL,
+        "INDIRECT_VIRTUAL",
+    ),
+    (
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_USB_TUNNEL,
+        "DISPLAYPORT_USB_TUNNEL",
+    ),
+    (DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL, "INTERNAL"),
+];
+
+/// Returns the name of an output technology, e.g. `"HDMI"` or `"DISPLAYPORT_EXTERNAL"`.\
+/// Values which are not known to this crate are named `"UNKNOWN"`.
+pub fn output_technology_name(t: DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY) -> &'static str {
+    OUTPUT_TECHNOLOGY_NAMES
+        .iter()
+        .find(|(technology, _)| *technology == t)
+        .map_or("UNKNOWN", |(_, name)| name)
+}
+
+/// Parses a name produced by `output_technology_name` back into an output technology.\
+/// The `DISPLAYCONFIG_OUTPUT_TECHNOLOGY_` prefix is optional and matching is case-insensitive.
+pub fn output_technology_from_name(s: &str) -> Option<DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY> {
+    let s = s.trim();
+    let s = s
+        .get(..PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(PREFIX))
+        .map_or(s, |_| &s[PREFIX.len()..]);
+    OUTPUT_TECHNOLOGY_NAMES
+        .iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(s))
+        .map(|(technology, _)| *technology)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for (technology, _) in OUTPUT_TECHNOLOGY_NAMES {
+            assert_eq!(
+                output_technology_from_name(output_technology_name(technology)),
+                Some(technology)
+            );
+        }
+    }
+
+    #[test]
+    fn from_name_ignores_case_and_prefix() {
+        for name in [
+            "hdmi",
+            " Hdmi ",
+            "DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HDMI",
+            "displayconfig_output_technology_hdmi",
+        ] {
+            assert_eq!(
+                output_technology_from_name(name),
+                Some(DISPLAYCONFIG_OUTPUT_TECHNOLOGY_HDMI)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_names_and_values() {
+        assert_eq!(output_technology_from_name("THUNDERBOLT"), None);
+        assert_eq!(output_technology_from_name("UNKNOWN"), None);
+        assert_eq!(output_technology_from_name(PREFIX), None);
+        assert_eq!(
+            output_technology_name(DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY(1234)),
+            "UNKNOWN"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use windows::Win32::Devices::Display::DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY;
+use windows::Win32::Devices::Display::QDC_ALL_PATHS;
+use windows::Win32::Devices::Display::QDC_ONLY_ACTIVE_PATHS;
+use windows::Win32::Devices::Display::QUERY_DISPLAY_CONFIG_FLAGS;
+use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_PATH_ACTIVE;
+
+use crate::device;
+use crate::device::wchar_to_string;
+use crate::error::SysError;
+
+/// Which `DISPLAYCONFIG` paths to return from `display_paths`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PathScope {
+    /// Only the paths of the current desktop (`QDC_ONLY_ACTIVE_PATHS`)
+    #[default]
+    ActiveOnly,
+    /// Every possible path, including inactive ones (`QDC_ALL_PATHS`). This is every combination
+    /// of source and target, so there are usually many more paths than monitors.
+    All,
+}
+
+impl From<PathScope> for QUERY_DISPLAY_CONFIG_FLAGS {
+    fn from(scope: PathScope) -> Self {
+        match scope {
+            PathScope::ActiveOnly => QDC_ONLY_ACTIVE_PATHS,
+            PathScope::All => QDC_ALL_PATHS,
+        }
+    }
+}
+
+/// A `DISPLAYCONFIG` path connecting a source (a GDI device, e.g. `\\.\DISPLAY1`) to a target
+/// (a monitor output of the adapter)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisplayPath {
+    /// Whether this path is part of the current desktop (`DISPLAYCONFIG_PATH_ACTIVE`)
+    pub active: bool,
+    /// Whether a monitor is connected to the target
+    pub target_available: bool,
+    /// The `(LowPart, HighPart)` of the adapter LUID of the target
+    pub adapter_id: (u32, i32),
+    pub source_id: u32,
+    pub target_id: u32,
+    /// The GDI device name of the source, or `None` if access was denied
+    pub source_gdi_name: Option<String>,
+    /// The Device Path of the monitor connected to the target, which is empty when there is none
+    /// (or access was denied)
+    pub target_device_path: String,
+    /// The `monitorFriendlyDeviceName` of the target, which is empty when it is unknown
+    pub friendly_name: String,
+    pub output_technology: DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY,
+}
+
+pub(crate) fn display_paths(scope: PathScope) -> Result<Vec<DisplayPath>, SysError> {
+    unsafe {
+        let (paths, _) = device::query_display_config(scope.into())?;
+        paths
+            .into_iter()
+            .map(|path| {
+                let source = path.sourceInfo;
+                let target = path.targetInfo;
+                let target_available = target.targetAvailable.as_bool();
+
+                // Targets without a monitor may not have a name to query, which is not an error
+                let target_name = match device::get_target_device_name(target.adapterId, target.id)
+                {
+                    Ok(target_name) => target_name,
+                    Err(_) if !target_available => None,
+                    Err(e) => return Err(e),
+                };
+
+                Ok(DisplayPath {
+                    active: path.flags & DISPLAYCONFIG_PATH_ACTIVE != 0,
+                    target_available,
+                    adapter_id: (target.adapterId.LowPart, target.adapterId.HighPart),
+                    source_id: source.id,
+                    target_id: target.id,
+                    source_gdi_name: device::get_source_gdi_name(source.adapterId, source.id)?,
+                    target_device_path: target_name
+                        .map(|name| wchar_to_string(&name.monitorDevicePath))
+                        .unwrap_or_default(),
+                    friendly_name: target_name
+                        .map(|name| wchar_to_string(&name.monitorFriendlyDeviceName))
+                        .unwrap_or_default(),
+                    output_technology: target.outputTechnology,
+                })
+            })
+            .collect()
+    }
+}
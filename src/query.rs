@@ -0,0 +1,83 @@
+use windows::Win32::Devices::Display::DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER;
+
+use crate::device;
+use crate::device::Device;
+use crate::device::DeviceFilter;
+use crate::device::PhysicalDevice;
+use crate::error::Error;
+use crate::error::SysError;
+
+/// A builder for customising how displays are enumerated.\
+/// By default every device must have matching `DISPLAYCONFIG` data, otherwise enumeration fails
+/// with a `DeviceInfoMissing` error.\
+/// Filters are combined, so a device is only returned when it matches all of them. With
+/// `collect_physical` they are checked before the physical monitor and file handles are opened.
+#[derive(Clone, Debug, Default)]
+pub struct DisplayQuery {
+    lenient: bool,
+    filter: DeviceFilter,
+}
+
+impl DisplayQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When enabled, devices with missing `DISPLAYCONFIG` data (e.g. when access is denied in
+    /// restricted or Remote Desktop sessions) are still returned, with their output technology
+    /// defaulted to `DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER`, instead of producing errors.
+    #[must_use]
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Only returns monitors which are not built-in panels (see `ConnectorType::Internal`).\
+    /// Monitors with an unknown output technology are considered external.
+    #[must_use]
+    pub fn external_only(mut self) -> Self {
+        self.filter.external_only = true;
+        self
+    }
+
+    /// Only returns the primary monitor, or every monitor showing it in "Duplicate" mode
+    #[must_use]
+    pub fn primary_only(mut self) -> Self {
+        self.filter.primary_only = true;
+        self
+    }
+
+    /// Only returns monitors whose current display mode is at least `width` by `height` pixels
+    #[must_use]
+    pub fn min_resolution(mut self, width: u32, height: u32) -> Self {
+        self.filter.min_resolution = Some((width, height));
+        self
+    }
+
+    pub fn collect_all(&self) -> Result<Vec<Device>, Error> {
+        device::connected_displays_all()
+            .map(|device| {
+                let mut device = device?;
+                if device.output_technology.is_none() {
+                    if !self.lenient {
+                        return Err(SysError::DeviceInfoMissing);
+                    }
+                    device.output_technology = Some(DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER);
+                }
+                Ok(device)
+            })
+            .filter(|device| {
+                device
+                    .as_ref()
+                    .map_or(true, |device| self.filter.matches_device(device))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(Into::into)
+    }
+
+    pub fn collect_physical(&self) -> Result<Vec<PhysicalDevice>, Error> {
+        device::connected_displays_physical_with(self.lenient, self.filter)
+            .collect::<Result<_, _>>()
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,119 @@
+use std::mem::size_of;
+use std::mem::size_of_val;
+
+use windows::Win32::Devices::Display::SetDisplayConfig;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_MODE_INFO;
+use windows::Win32::Devices::Display::DISPLAYCONFIG_PATH_INFO;
+use windows::Win32::Devices::Display::QDC_ONLY_ACTIVE_PATHS;
+use windows::Win32::Devices::Display::SDC_APPLY;
+use windows::Win32::Devices::Display::SDC_USE_SUPPLIED_DISPLAY_CONFIG;
+use windows::Win32::Foundation::WIN32_ERROR;
+
+use crate::device;
+use crate::error::SysError;
+
+/// A snapshot of the active `DISPLAYCONFIG` paths and modes, which can be reapplied in one shot
+/// with `apply_topology`.\
+/// The structs are stored as raw bytes so that the snapshot can be persisted (with the `serde`
+/// feature) without depending on the `windows` types.\
+/// Note: paths refer to adapters by LUID, which can change across reboots and driver updates, in
+/// which case applying a persisted topology will fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Topology {
+    paths: Vec<u8>,
+    modes: Vec<u8>,
+}
+
+impl Topology {
+    /// The number of active paths in this topology
+    pub fn path_count(&self) -> usize {
+        self.paths.len() / size_of::<DISPLAYCONFIG_PATH_INFO>()
+    }
+
+    /// The number of source and target modes in this topology
+    pub fn mode_count(&self) -> usize {
+        self.modes.len() / size_of::<DISPLAYCONFIG_MODE_INFO>()
+    }
+}
+
+pub(crate) fn capture_topology() -> Result<Topology, SysError> {
+    let (paths, modes) = unsafe { device::query_display_config(QDC_ONLY_ACTIVE_PATHS) }?;
+    Ok(Topology {
+        paths: to_bytes(&paths),
+        modes: to_bytes(&modes),
+    })
+}
+
+pub(crate) fn apply_topology(topology: &Topology) -> Result<(), SysError> {
+    let paths = from_bytes::<DISPLAYCONFIG_PATH_INFO>(&topology.paths)?;
+    let modes = from_bytes::<DISPLAYCONFIG_MODE_INFO>(&topology.modes)?;
+    unsafe {
+        WIN32_ERROR(SetDisplayConfig(
+            Some(&paths),
+            Some(&modes),
+            SDC_APPLY | SDC_USE_SUPPLIED_DISPLAY_CONFIG,
+        ) as u32)
+        .ok()
+        .map_err(SysError::SetDisplayConfigFailed)
+    }
+}
+
+/// Copies a slice of plain `DISPLAYCONFIG` structs into bytes
+fn to_bytes<T: Copy>(items: &[T]) -> Vec<u8> {
+    // SAFETY: the DISPLAYCONFIG structs are plain `repr(C)` data with no pointers
+    unsafe { std::slice::from_raw_parts(items.as_ptr().cast::<u8>(), size_of_val(items)) }.to_vec()
+}
+
+/// Copies bytes produced by `to_bytes` back into plain `DISPLAYCONFIG` structs
+fn from_bytes<T: Copy + Default>(bytes: &[u8]) -> Result<Vec<T>, SysError> {
+    let len = bytes.len() / size_of::<T>();
+    if len * size_of::<T>() != bytes.len() {
+        return Err(SysError::InvalidTopology);
+    }
+
+    let mut items = vec![T::default(); len];
+    // SAFETY: every bit pattern is a valid DISPLAYCONFIG struct, and the lengths match exactly
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), items.as_mut_ptr().cast::<u8>(), bytes.len());
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Graphics::Gdi::DISPLAYCONFIG_PATH_ACTIVE;
+
+    use super::*;
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut paths = [DISPLAYCONFIG_PATH_INFO::default(); 2];
+        paths[0].sourceInfo.id = 1;
+        paths[1].targetInfo.id = 0x1234_5678;
+        paths[1].flags = DISPLAYCONFIG_PATH_ACTIVE;
+
+        let bytes = to_bytes(&paths);
+        assert_eq!(bytes.len(), 2 * size_of::<DISPLAYCONFIG_PATH_INFO>());
+
+        let round_tripped = from_bytes::<DISPLAYCONFIG_PATH_INFO>(&bytes).unwrap();
+        assert_eq!(round_tripped.len(), 2);
+        assert_eq!(round_tripped[0].sourceInfo.id, 1);
+        assert_eq!(round_tripped[1].targetInfo.id, 0x1234_5678);
+        assert_eq!(round_tripped[1].flags, DISPLAYCONFIG_PATH_ACTIVE);
+        assert_eq!(to_bytes(&round_tripped), bytes);
+    }
+
+    #[test]
+    fn bytes_of_a_partial_struct_are_rejected() {
+        let bytes = to_bytes(&[DISPLAYCONFIG_MODE_INFO::default()]);
+
+        assert!(matches!(
+            from_bytes::<DISPLAYCONFIG_MODE_INFO>(&bytes[1..]),
+            Err(SysError::InvalidTopology)
+        ));
+        assert!(from_bytes::<DISPLAYCONFIG_MODE_INFO>(&[])
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+use windows::Win32::Devices::Display::CapabilitiesRequestAndCapabilitiesReply;
+use windows::Win32::Devices::Display::GetCapabilitiesStringLength;
+use windows::Win32::Devices::Display::GetVCPFeatureAndVCPFeatureReply;
+use windows::Win32::Devices::Display::SetVCPFeature;
+use windows::Win32::Foundation::ERROR_GRAPHICS_DDCCI_VCP_NOT_SUPPORTED;
+use windows::Win32::Foundation::HANDLE;
+
+use crate::error::SysError;
+
+/// MCCS "Display Scaling", see `PhysicalDevice::get_overscan`
+pub(crate) const DISPLAY_SCALING: u8 = 0x86;
+/// MCCS "Color Temperature Increment", the size of one step of `USER_COLOR_TEMPERATURE` in Kelvin
+const COLOR_TEMPERATURE_INCREMENT: u8 = 0x0B;
+/// MCCS "User Color Temperature", the number of increments above 3000K
+const USER_COLOR_TEMPERATURE: u8 = 0x0C;
+/// MCCS "Select Color Preset"
+const SELECT_COLOR_PRESET: u8 = 0x14;
+
+/// The base of the `USER_COLOR_TEMPERATURE` scale in Kelvin
+const USER_COLOR_TEMPERATURE_BASE: u32 = 3000;
+
+/// The value of a VCP feature read over DDC/CI
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VcpReply {
+    pub current: u32,
+    pub maximum: u32,
+}
+
+/// Reads the value of a VCP feature over DDC/CI.\
+/// A `None` value means the monitor reports the feature as unsupported.
+pub(crate) unsafe fn get_vcp_feature(
+    handle: HANDLE,
+    code: u8,
+) -> Result<Option<VcpReply>, SysError> {
+    let mut current = 0;
+    let mut maximum = 0;
+    if GetVCPFeatureAndVCPFeatureReply(handle, code, None, &mut current, Some(&mut maximum)) == 0 {
+        let e = windows::core::Error::from_win32();
+        return if e.code() == ERROR_GRAPHICS_DDCCI_VCP_NOT_SUPPORTED {
+            Ok(None)
+        } else {
+            Err(SysError::GettingVcpFeatureFailed { code, source: e })
+        };
+    }
+    Ok(Some(VcpReply { current, maximum }))
+}
+
+/// Asks the monitor for the length of its capabilities string, which is a cheap way to check that
+/// it responds over DDC/CI at all.\
+/// A length of zero is reported as an error, as no usable capabilities can be read.
+pub(crate) unsafe fn get_capabilities_string_length(handle: HANDLE) -> Result<u32, SysError> {
+    let mut length = 0;
+    if GetCapabilitiesStringLength(handle, &mut length) == 0 {
+        return Err(SysError::GettingCapabilitiesFailed(
+            windows::core::Error::from_win32(),
+        ));
+    }
+    if length == 0 {
+        return Err(SysError::CapabilitiesEmpty);
+    }
+    Ok(length)
+}
+
+/// Reads the raw MCCS capabilities string of a monitor, without its terminating null
+pub(crate) unsafe fn get_capabilities_string(handle: HANDLE) -> Result<String, SysError> {
+    // The length includes the terminating null
+    let length = get_capabilities_string_length(handle)?;
+    let mut buffer = vec![0; length as usize];
+    if CapabilitiesRequestAndCapabilitiesReply(handle, &mut buffer) == 0 {
+        return Err(SysError::GettingCapabilitiesFailed(
+            windows::core::Error::from_win32(),
+        ));
+    }
+
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    let capabilities = String::from_utf8_lossy(&buffer[..end]).trim().to_string();
+    if capabilities.is_empty() {
+        return Err(SysError::CapabilitiesEmpty);
+    }
+    Ok(capabilities)
+}
+
+/// Writes the value of a VCP feature over DDC/CI
+pub(crate) unsafe fn set_vcp_feature(handle: HANDLE, code: u8, value: u32) -> Result<(), SysError> {
+    if SetVCPFeature(handle, code, value) == 0 {
+        return Err(SysError::SettingVcpFeatureFailed {
+            code,
+            source: windows::core::Error::from_win32(),
+        });
+    }
+    Ok(())
+}
+
+/// Reads the current color temperature of a monitor in Kelvin.\
+/// The user color temperature (VCP `0x0C`) is preferred, falling back to the selected color
+/// preset (VCP `0x14`) when the monitor doesn't support it.\
+/// A `None` value means neither is supported, or the active preset has no fixed temperature
+/// (e.g. "native" or a user preset).\
+/// A zero increment or step count, or a user color temperature which overflows a `u32`, is a
+/// garbage reply from a monitor which doesn't really support it, so the preset is used instead.
+pub(crate) unsafe fn get_color_temperature(handle: HANDLE) -> Result<Option<u32>, SysError> {
+    let increment = get_vcp_feature(handle, COLOR_TEMPERATURE_INCREMENT)?;
+    if let Some(increment) = increment.filter(|increment| increment.current != 0) {
+        let steps = get_vcp_feature(handle, USER_COLOR_TEMPERATURE)?;
+        if let Some(steps) = steps.filter(|steps| steps.current != 0) {
+            let kelvin = steps
+                .current
+                .checked_mul(increment.current)
+                .and_then(|delta| delta.checked_add(USER_COLOR_TEMPERATURE_BASE));
+            if kelvin.is_some() {
+                return Ok(kelvin);
+            }
+        }
+    }
+
+    Ok(get_vcp_feature(handle, SELECT_COLOR_PRESET)?
+        .and_then(|preset| color_preset_temperature(preset.current)))
+}
+
+/// Maps an MCCS color preset to its approximate color temperature in Kelvin
+fn color_preset_temperature(preset: u32) -> Option<u32> {
+    match preset & 0xFF {
+        // sRGB is defined with a D65 white point
+        0x01 | 0x05 => Some(6500),
+        0x03 => Some(4000),
+        0x04 => Some(5000),
+        0x06 => Some(7500),
+        0x07 => Some(8200),
+        0x08 => Some(9300),
+        0x09 => Some(10000),
+        0x0A => Some(11500),
+        _ => None,
+    }
+}
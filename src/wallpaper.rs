@@ -0,0 +1,97 @@
+use std::os::windows::ffi::OsStrExt;
+
+use windows::core::PCWSTR;
+use windows::Win32::System::Com::CoCreateInstance;
+use windows::Win32::System::Com::CLSCTX_ALL;
+use windows::Win32::UI::Shell::DesktopWallpaper;
+use windows::Win32::UI::Shell::IDesktopWallpaper;
+
+use crate::com::take_pwstr;
+use crate::com::ComGuard;
+use crate::device::Device;
+use crate::error::Error;
+use crate::error::SysError;
+
+impl Device {
+    /// Returns the path of the wallpaper shown on this monitor, using `IDesktopWallpaper`.\
+    /// A `None` value means no wallpaper image is set (e.g. a solid color background).\
+    /// Fails with `Error::WallpaperFailed` when `IDesktopWallpaper` does not know this monitor.
+    pub fn wallpaper_path(&self) -> Result<Option<String>, Error> {
+        unsafe {
+            let _com = ComGuard::new();
+            let wallpaper = desktop_wallpaper()?;
+            let monitor_id = monitor_id_for_device_path(&wallpaper, &self.device_path)?
+                .ok_or_else(|| SysError::WallpaperMonitorNotFound {
+                    device_path: self.device_path.clone(),
+                })?;
+
+            let path = wallpaper
+                .GetWallpaper(PCWSTR(monitor_id.as_ptr()))
+                .map_err(SysError::DesktopWallpaperFailed)?;
+            let path = take_pwstr(path);
+
+            Ok((!path.is_empty()).then_some(path))
+        }
+    }
+
+    /// Sets the wallpaper shown on this monitor, using `IDesktopWallpaper`.\
+    /// The path must point to an existing image file; relative paths are resolved against the
+    /// current working directory.
+    pub fn set_wallpaper(&self, path: &str) -> Result<(), Error> {
+        let path = std::path::absolute(path)
+            .ok()
+            .filter(|path| path.is_file())
+            .ok_or_else(|| SysError::WallpaperFileNotFound {
+                path: path.to_string(),
+            })?;
+        let path = path
+            .as_os_str()
+            .encode_wide()
+            .chain(Some(0))
+            .collect::<Vec<_>>();
+
+        unsafe {
+            let _com = ComGuard::new();
+            let wallpaper = desktop_wallpaper()?;
+            let monitor_id = monitor_id_for_device_path(&wallpaper, &self.device_path)?
+                .ok_or_else(|| SysError::WallpaperMonitorNotFound {
+                    device_path: self.device_path.clone(),
+                })?;
+
+            wallpaper
+                .SetWallpaper(PCWSTR(monitor_id.as_ptr()), PCWSTR(path.as_ptr()))
+                .map_err(SysError::DesktopWallpaperFailed)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) unsafe fn desktop_wallpaper() -> Result<IDesktopWallpaper, SysError> {
+    CoCreateInstance(&DesktopWallpaper, None, CLSCTX_ALL).map_err(SysError::DesktopWallpaperFailed)
+}
+
+/// Finds the `IDesktopWallpaper` monitor ID matching a device path, returned as a null-terminated
+/// wide string.\
+/// The monitor IDs are device paths themselves, but may differ from ours in casing.
+pub(crate) unsafe fn monitor_id_for_device_path(
+    wallpaper: &IDesktopWallpaper,
+    device_path: &str,
+) -> Result<Option<Vec<u16>>, SysError> {
+    let count = wallpaper
+        .GetMonitorDevicePathCount()
+        .map_err(SysError::DesktopWallpaperFailed)?;
+
+    for idx in 0..count {
+        let monitor_id = wallpaper
+            .GetMonitorDevicePathAt(idx)
+            .map_err(SysError::DesktopWallpaperFailed)?;
+        let monitor_id = take_pwstr(monitor_id);
+
+        if monitor_id.eq_ignore_ascii_case(device_path) {
+            return Ok(Some(monitor_id.encode_utf16().chain(Some(0)).collect()));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,510 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::thread::JoinHandle;
+
+use windows::core::w;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::ERROR_CLASS_ALREADY_EXISTS;
+use windows::Win32::Foundation::HINSTANCE;
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Foundation::LPARAM;
+use windows::Win32::Foundation::LRESULT;
+use windows::Win32::Foundation::WPARAM;
+use windows::Win32::Graphics::Gdi::MonitorFromWindow;
+use windows::Win32::Graphics::Gdi::MONITOR_DEFAULTTONULL;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::Accessibility::SetWinEventHook;
+use windows::Win32::UI::Accessibility::UnhookWinEvent;
+use windows::Win32::UI::Accessibility::HWINEVENTHOOK;
+use windows::Win32::UI::HiDpi::SetThreadDpiAwarenessContext;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2;
+use windows::Win32::UI::WindowsAndMessaging::CreateWindowExW;
+use windows::Win32::UI::WindowsAndMessaging::DefWindowProcW;
+use windows::Win32::UI::WindowsAndMessaging::DestroyWindow;
+use windows::Win32::UI::WindowsAndMessaging::DispatchMessageW;
+use windows::Win32::UI::WindowsAndMessaging::GetMessageW;
+use windows::Win32::UI::WindowsAndMessaging::KillTimer;
+use windows::Win32::UI::WindowsAndMessaging::PostThreadMessageW;
+use windows::Win32::UI::WindowsAndMessaging::RegisterClassW;
+use windows::Win32::UI::WindowsAndMessaging::SetTimer;
+use windows::Win32::UI::WindowsAndMessaging::TranslateMessage;
+use windows::Win32::UI::WindowsAndMessaging::EVENT_SYSTEM_FOREGROUND;
+use windows::Win32::UI::WindowsAndMessaging::HMENU;
+use windows::Win32::UI::WindowsAndMessaging::MSG;
+use windows::Win32::UI::WindowsAndMessaging::WINDOW_EX_STYLE;
+use windows::Win32::UI::WindowsAndMessaging::WINDOW_STYLE;
+use windows::Win32::UI::WindowsAndMessaging::WINEVENT_OUTOFCONTEXT;
+use windows::Win32::UI::WindowsAndMessaging::WM_DEVICECHANGE;
+use windows::Win32::UI::WindowsAndMessaging::WM_DISPLAYCHANGE;
+use windows::Win32::UI::WindowsAndMessaging::WM_DPICHANGED;
+use windows::Win32::UI::WindowsAndMessaging::WM_QUIT;
+use windows::Win32::UI::WindowsAndMessaging::WM_SETTINGCHANGE;
+use windows::Win32::UI::WindowsAndMessaging::WM_TIMER;
+use windows::Win32::UI::WindowsAndMessaging::WNDCLASSW;
+use windows::Win32::UI::WindowsAndMessaging::WNDPROC;
+
+use crate::device;
+use crate::device::Device;
+use crate::error::Error;
+use crate::error::SysError;
+
+struct ForegroundState {
+    /// Taken out while it runs, see `foreground_event_proc`
+    callback: Option<Box<dyn FnMut(Device)>>,
+    last_hmonitor: isize,
+}
+
+struct DpiState {
+    sender: Sender<DpiChange>,
+    /// The last known DPI of every device, by Device Path
+    dpis: HashMap<String, u32>,
+}
+
+struct DisplayState {
+    /// Taken out while it runs, see `report_display_change`
+    callback: Option<Box<dyn FnMut(DisplayEvent)>>,
+    primary: Option<Device>,
+}
+
+thread_local! {
+    // WinEvent callbacks carry no user data, so the state lives on the hook's own thread
+    static FOREGROUND_STATE: RefCell<Option<ForegroundState>> = const { RefCell::new(None) };
+    // Likewise for the window procedure of the DPI watcher's hidden window
+    static DPI_STATE: RefCell<Option<DpiState>> = const { RefCell::new(None) };
+    static DISPLAY_STATE: RefCell<Option<DisplayState>> = const { RefCell::new(None) };
+}
+
+/// A dedicated thread running a message pump, which is stopped with `WM_QUIT` and joined on `Drop`
+struct MessageThread {
+    thread_id: u32,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MessageThread {
+    /// Spawns the thread and runs `setup` on it, returning once `setup` has completed so that its
+    /// errors can be reported to the caller.\
+    /// The teardown returned by `setup` runs on the thread after the message pump exits.
+    fn spawn<S, T>(setup: S) -> Result<Self, Error>
+    where
+        S: FnOnce() -> Result<T, SysError> + Send + 'static,
+        T: FnOnce(),
+    {
+        let (tx, rx) = mpsc::channel();
+
+        let thread = std::thread::spawn(move || unsafe {
+            let teardown = match setup() {
+                Ok(teardown) => teardown,
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                    return;
+                }
+            };
+
+            let _ = tx.send(Ok(GetCurrentThreadId()));
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            teardown();
+        });
+
+        match rx.recv() {
+            Ok(Ok(thread_id)) => Ok(Self {
+                thread_id,
+                thread: Some(thread),
+            }),
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e.into())
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(SysError::WatcherThreadFailed.into())
+            }
+        }
+    }
+}
+
+impl Drop for MessageThread {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Watches for the foreground window moving to a different monitor using an
+/// `EVENT_SYSTEM_FOREGROUND` WinEvent hook.\
+/// The hook and its message pump run on a dedicated thread, and the callback is invoked on that
+/// thread with the newly focused `Device` only when the foreground monitor actually changes.\
+/// The hook is uninstalled and the thread is joined on `Drop`.
+pub struct ForegroundMonitorWatcher {
+    thread: MessageThread,
+}
+
+impl std::fmt::Debug for ForegroundMonitorWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForegroundMonitorWatcher")
+            .field("thread_id", &self.thread.thread_id)
+            .finish()
+    }
+}
+
+impl ForegroundMonitorWatcher {
+    pub fn new(callback: impl FnMut(Device) + Send + 'static) -> Result<Self, Error> {
+        let thread = MessageThread::spawn(move || unsafe {
+            let hook = SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                HMODULE::default(),
+                Some(foreground_event_proc),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT,
+            );
+
+            if hook.is_invalid() {
+                return Err(SysError::SetWinEventHookFailed(
+                    windows::core::Error::from_win32(),
+                ));
+            }
+
+            FOREGROUND_STATE.with(|state| {
+                *state.borrow_mut() = Some(ForegroundState {
+                    callback: Some(Box::new(callback)),
+                    last_hmonitor: 0,
+                });
+            });
+
+            Ok(move || {
+                let _ = UnhookWinEvent(hook);
+                FOREGROUND_STATE.with(|state| state.borrow_mut().take());
+            })
+        })?;
+
+        Ok(Self { thread })
+    }
+}
+
+unsafe extern "system" fn foreground_event_proc(
+    _: HWINEVENTHOOK,
+    _: u32,
+    hwnd: HWND,
+    _: i32,
+    _: i32,
+    _: u32,
+    _: u32,
+) {
+    let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+    if hmonitor.is_invalid() {
+        return;
+    }
+
+    // The callback is taken out of the state while it runs, as WinEvents are delivered while it
+    // pumps messages (e.g. showing a MessageBox), which would otherwise re-enter the borrow. Events
+    // arriving in the meantime are skipped, without updating `last_hmonitor`.
+    let Some((mut callback, device)) = FOREGROUND_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let state = state.as_mut()?;
+        if state.last_hmonitor == hmonitor.0 as isize || state.callback.is_none() {
+            return None;
+        }
+
+        let device = device::device_from_hmonitor(hmonitor).ok().flatten()?;
+        state.last_hmonitor = hmonitor.0 as isize;
+        Some((state.callback.take()?, device))
+    }) else {
+        return;
+    };
+
+    callback(device);
+
+    FOREGROUND_STATE.with(|state| {
+        if let Some(state) = state.borrow_mut().as_mut() {
+            state.callback = Some(callback);
+        }
+    });
+}
+
+/// A change of a monitor's effective DPI, e.g. because the user changed its scaling
+#[derive(Debug)]
+pub struct DpiChange {
+    pub device: Device,
+    pub old_dpi: u32,
+    pub new_dpi: u32,
+}
+
+/// Watches for monitors changing their effective DPI.\
+/// A hidden window on a dedicated, per-monitor DPI aware thread listens for `WM_DPICHANGED`,
+/// `WM_DISPLAYCHANGE` and `WM_SETTINGCHANGE`, after which the DPI of every monitor is compared
+/// with the previous snapshot and each change is sent to `receiver`.\
+/// The window is destroyed and the thread is joined on `Drop`.
+pub struct DpiChangeWatcher {
+    thread: MessageThread,
+    receiver: Receiver<DpiChange>,
+}
+
+impl std::fmt::Debug for DpiChangeWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DpiChangeWatcher")
+            .field("thread_id", &self.thread.thread_id)
+            .finish()
+    }
+}
+
+impl DpiChangeWatcher {
+    pub fn new() -> Result<Self, Error> {
+        let (sender, receiver) = mpsc::channel();
+
+        let thread = MessageThread::spawn(move || unsafe {
+            // Without per-monitor awareness GetDpiForMonitor always reports 96 DPI
+            SetThreadDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+
+            let hwnd =
+                create_hidden_window(w!("win32-display-data-dpi-watcher"), Some(dpi_window_proc))?;
+
+            let mut state = DpiState {
+                sender,
+                dpis: HashMap::new(),
+            };
+            state.update();
+            DPI_STATE.with(|s| *s.borrow_mut() = Some(state));
+
+            Ok(move || {
+                let _ = DestroyWindow(hwnd);
+                DPI_STATE.with(|state| state.borrow_mut().take());
+            })
+        })?;
+
+        Ok(Self { thread, receiver })
+    }
+
+    /// The receiving end of the channel DPI changes are sent to
+    pub fn receiver(&self) -> &Receiver<DpiChange> {
+        &self.receiver
+    }
+}
+
+impl DpiState {
+    /// Takes a new snapshot of every monitor's DPI, sending any changes from the previous one
+    fn update(&mut self) {
+        for device in device::connected_displays_all().flatten() {
+            let Ok(geometry) = device.geometry() else {
+                continue;
+            };
+
+            let new_dpi = geometry.dpi.0;
+            let old_dpi = self.dpis.insert(device.device_path.clone(), new_dpi);
+            if let Some(old_dpi) = old_dpi.filter(|old_dpi| *old_dpi != new_dpi) {
+                let _ = self.sender.send(DpiChange {
+                    device,
+                    old_dpi,
+                    new_dpi,
+                });
+            }
+        }
+    }
+}
+
+unsafe extern "system" fn dpi_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if matches!(msg, WM_DPICHANGED | WM_DISPLAYCHANGE | WM_SETTINGCHANGE) {
+        DPI_STATE.with(|state| {
+            if let Some(state) = state.borrow_mut().as_mut() {
+                state.update();
+            }
+        });
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+/// An event reported by a `DisplayWatcher`
+#[derive(Debug)]
+// Events are rare, so boxing the devices isn't worth the less ergonomic matching
+#[allow(clippy::large_enum_variant)]
+pub enum DisplayEvent {
+    /// The display configuration changed (e.g. a monitor was connected, disconnected or changed
+    /// resolution)
+    DisplaysChanged,
+    /// The primary monitor changed, which moves the origin of the virtual desktop coordinates.\
+    /// This is reported after the corresponding `DisplaysChanged`.
+    PrimaryChanged { old: Device, new: Device },
+}
+
+/// Watches for changes to the display configuration.\
+/// A hidden window on a dedicated thread listens for `WM_DISPLAYCHANGE` and `WM_DEVICECHANGE`, and
+/// the callback is invoked on that thread with every `DisplayEvent`. Windows sends several of these
+/// messages while a monitor is (dis)connected, so each burst is coalesced into a single
+/// `DisplaysChanged` once no message has arrived for 200ms. The primary monitor is compared across
+/// snapshots to report `PrimaryChanged` only when it actually changes.\
+/// The window is destroyed and the thread is joined on `Drop`.
+pub struct DisplayWatcher {
+    thread: MessageThread,
+}
+
+impl std::fmt::Debug for DisplayWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DisplayWatcher")
+            .field("thread_id", &self.thread.thread_id)
+            .finish()
+    }
+}
+
+impl DisplayWatcher {
+    pub fn new(callback: impl FnMut(DisplayEvent) + Send + 'static) -> Result<Self, Error> {
+        let thread = MessageThread::spawn(move || unsafe {
+            let hwnd = create_hidden_window(
+                w!("win32-display-data-display-watcher"),
+                Some(display_window_proc),
+            )?;
+
+            DISPLAY_STATE.with(|state| {
+                *state.borrow_mut() = Some(DisplayState {
+                    callback: Some(Box::new(callback)),
+                    primary: primary_display(),
+                });
+            });
+
+            Ok(move || {
+                let _ = DestroyWindow(hwnd);
+                DISPLAY_STATE.with(|state| state.borrow_mut().take());
+            })
+        })?;
+
+        Ok(Self { thread })
+    }
+}
+
+/// The ID of the timer restarted by every display change message of a `DisplayWatcher`
+const DISPLAY_CHANGE_TIMER_ID: usize = 1;
+/// How long a `DisplayWatcher` waits for further display change messages before reporting them
+const DISPLAY_CHANGE_DEBOUNCE_MS: u32 = 200;
+
+fn primary_display() -> Option<Device> {
+    device::connected_displays_all()
+        .flatten()
+        .find(|device| device.is_primary)
+}
+
+unsafe extern "system" fn display_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_DISPLAYCHANGE || msg == WM_DEVICECHANGE {
+        // Restarting the timer pushes the report back until the burst of messages is over
+        SetTimer(
+            hwnd,
+            DISPLAY_CHANGE_TIMER_ID,
+            DISPLAY_CHANGE_DEBOUNCE_MS,
+            None,
+        );
+    } else if msg == WM_TIMER && wparam.0 == DISPLAY_CHANGE_TIMER_ID {
+        let _ = KillTimer(hwnd, DISPLAY_CHANGE_TIMER_ID);
+        report_display_change(hwnd);
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+/// Sends `DisplaysChanged`, followed by `PrimaryChanged` if the primary monitor changed, to the
+/// callback of a `DisplayWatcher`.\
+/// The callback is taken out of the state while it runs, as the window procedure is re-entered
+/// when it pumps messages (e.g. showing a MessageBox), and the state is not borrowed across it.
+/// A change reported in the meantime restarts the timer, so it is reported once the callback is
+/// back.
+unsafe fn report_display_change(hwnd: HWND) {
+    let callback = DISPLAY_STATE.with(|state| {
+        state
+            .borrow_mut()
+            .as_mut()
+            .map(|state| state.callback.take())
+    });
+    let mut callback = match callback {
+        Some(Some(callback)) => callback,
+        Some(None) => {
+            SetTimer(
+                hwnd,
+                DISPLAY_CHANGE_TIMER_ID,
+                DISPLAY_CHANGE_DEBOUNCE_MS,
+                None,
+            );
+            return;
+        }
+        None => return,
+    };
+
+    callback(DisplayEvent::DisplaysChanged);
+
+    // Keep the last known primary while none can be found (e.g. mid-reconfiguration)
+    if let Some(primary) = primary_display() {
+        let old = DISPLAY_STATE.with(|state| {
+            state
+                .borrow_mut()
+                .as_mut()
+                .and_then(|state| state.primary.replace(primary.clone()))
+        });
+        if let Some(old) = old.filter(|old| old.device_path != primary.device_path) {
+            callback(DisplayEvent::PrimaryChanged { old, new: primary });
+        }
+    }
+
+    DISPLAY_STATE.with(|state| {
+        if let Some(state) = state.borrow_mut().as_mut() {
+            state.callback = Some(callback);
+        }
+    });
+}
+
+/// Creates a hidden top-level window, which (unlike a message-only window) also receives
+/// broadcast messages such as `WM_DISPLAYCHANGE`.
+unsafe fn create_hidden_window(class_name: PCWSTR, wndproc: WNDPROC) -> Result<HWND, SysError> {
+    let instance = HINSTANCE::from(GetModuleHandleW(None).map_err(SysError::CreateWindowFailed)?);
+    let class = WNDCLASSW {
+        lpfnWndProc: wndproc,
+        hInstance: instance,
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+
+    // The class is still registered when a previous watcher of the same kind has been created
+    if RegisterClassW(&class) == 0 {
+        let e = windows::core::Error::from_win32();
+        if e.code() != ERROR_CLASS_ALREADY_EXISTS.to_hresult() {
+            return Err(SysError::CreateWindowFailed(e));
+        }
+    }
+
+    CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        class_name,
+        class_name,
+        WINDOW_STYLE::default(),
+        0,
+        0,
+        0,
+        0,
+        HWND::default(),
+        HMENU::default(),
+        instance,
+        None,
+    )
+    .map_err(SysError::CreateWindowFailed)
+}